@@ -14,6 +14,102 @@ pub enum GameError {
     // Error 2
     #[error("Ship is not a valid target ship")]
     InvalidTarget,
+
+    // Error 3
+    #[error("Action is not allowed in the current phase")]
+    WrongPhase,
+
+    // Error 4
+    #[error("Revealed data does not match the commitment")]
+    InvalidReveal,
+
+    // Error 5
+    #[error("Deadline has not passed yet")]
+    DeadlineNotReached,
+
+    // Error 6
+    #[error("Maneuver is outside of the ship's limits")]
+    InvalidManeuver,
+
+    // Error 7
+    #[error("No maneuver was submitted for a ship")]
+    MissingManeuver,
+
+    // Error 8
+    #[error("Signer is not a player in this session")]
+    NotAPlayer,
+
+    // Error 9
+    #[error("Ship does not belong to the signer")]
+    NotShipOwner,
+
+    // Error 10
+    #[error("Account is not the rent payer of this session")]
+    InvalidRentPayer,
+
+    // Error 11
+    #[error("Player has run out of time")]
+    OutOfTime,
+
+    // Error 12
+    #[error("Target is fully hidden from the shooter")]
+    NoLineOfSight,
+
+    // Error 13
+    #[error("Fleet has too few or too many ships for this queue")]
+    InvalidFleetSize,
+
+    // Error 14
+    #[error("Fleet costs more points than the queue allows")]
+    FleetOverBudget,
+
+    // Error 15
+    #[error("Ship can't be placed there")]
+    InvalidPlacement,
+
+    // Error 16
+    #[error("Upgrade does not exist")]
+    UnknownUpgrade,
+
+    // Error 17
+    #[error("Ship can't stack this upgrade any further")]
+    UpgradeLimitReached,
+
+    // Error 18
+    #[error("Ship is missing the upgrade this one builds on")]
+    MissingPrerequisite,
+
+    // Error 19
+    #[error("Player doesn't have enough scrap")]
+    NotEnoughScrap,
+
+    // Error 20
+    #[error("Ship has no weapon mount with that index")]
+    InvalidWeapon,
+
+    // Error 21
+    #[error("Ship's crew is too injured to upgrade this turn")]
+    CrewInjured,
+
+    // Error 22
+    #[error("Player already committed for this phase")]
+    AlreadyCommitted,
+
+    // Error 23
+    #[error("Player already revealed for this phase")]
+    AlreadyRevealed,
+
+    // Error 24
+    #[error("Rolls can't be made through another program")]
+    NestedInvocation,
+
+    // Error 25
+    #[error("Session rules are out of range")]
+    InvalidRules,
+
+    // Error 26
+    #[error("Rolls have to be the only instruction in their transaction")]
+    SharedTransaction,
 }
 
 impl From<GameError> for ProgramError {
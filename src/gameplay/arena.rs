@@ -0,0 +1,443 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::error::GameError;
+use crate::gameplay::deployment::deployment_zone;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::seat::Seat;
+use crate::gameplay::ship::SHIP_RADIUS;
+use crate::utils::vec2::Vector2;
+use solana_program::msg;
+
+// Playable area, rectangles and circles are centred on the origin
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum ArenaShape {
+    Rectangle { half_width: f32, half_height: f32 },
+    Circle { radius: f32 },
+    // Corners in order, either clockwise or counter clockwise
+    Polygon { points: Vec<Vector2> },
+}
+
+// What happens to a ship that ends its movement outside of the arena
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum OutOfBoundsRule {
+    // The ship is destroyed right away
+    Destroy,
+    // The ship takes damage for every turn it spends outside
+    DamagePerTurn,
+    // The ship bounces off the edge back into the arena
+    Bounce,
+    // The ship comes back in on the opposite side
+    Wrap,
+}
+
+// How far and in how many directions to look for a free spot for a ship that bounced or wrapped
+pub const FREE_SPOT_RINGS: u32 = 10;
+pub const FREE_SPOT_DIRECTIONS: u32 = 16;
+
+pub fn default_arena() -> ArenaShape {
+    ArenaShape::Rectangle {
+        half_width: 42.0,
+        half_height: 73.0,
+    }
+}
+
+// Whether two segments cross or touch
+fn segments_intersect(a: &Vector2, b: &Vector2, c: &Vector2, d: &Vector2) -> bool {
+    let side = |from: &Vector2, to: &Vector2, point: &Vector2| {
+        let edge = to.subtract(from);
+        let offset = point.subtract(from);
+        edge.x * offset.y - edge.y * offset.x
+    };
+
+    side(c, d, a) * side(c, d, b) <= 0.0 && side(a, b, c) * side(a, b, d) <= 0.0
+}
+
+impl ArenaShape {
+    // Arenas are picked by the players, so they need a real size and room for both deployment zones
+    pub fn validate(&self) -> Result<(), GameError> {
+        let has_size = match self {
+            ArenaShape::Rectangle {
+                half_width,
+                half_height,
+            } => {
+                half_width.is_finite()
+                    && half_height.is_finite()
+                    && *half_width > 0.0
+                    && *half_height > 0.0
+            }
+            ArenaShape::Circle { radius } => radius.is_finite() && *radius > 0.0,
+            ArenaShape::Polygon { points } => {
+                points.len() >= 3 && points.iter().all(|p| p.x.is_finite() && p.y.is_finite())
+            }
+        };
+        if !has_size {
+            msg!("Arena needs a positive size, and polygons at least 3 points");
+            return Err(GameError::InvalidRules);
+        }
+
+        for seat in [Seat::Player1, Seat::Player2] {
+            let zone = deployment_zone(seat);
+            let corners = zone.corners();
+            if !corners.iter().all(|c| self.contains(c)) {
+                msg!("Arena has to contain both deployment zones");
+                return Err(GameError::InvalidRules);
+            }
+
+            // With every corner inside, a polygon can still cut into the zone with a notch
+            if let ArenaShape::Polygon { points } = self {
+                for i in 0..points.len() {
+                    let a = &points[i];
+                    let b = &points[(i + 1) % points.len()];
+                    let crosses_zone = (0..corners.len()).any(|j| {
+                        segments_intersect(a, b, &corners[j], &corners[(j + 1) % corners.len()])
+                    });
+                    if crosses_zone {
+                        msg!("Arena edge cuts through a deployment zone");
+                        return Err(GameError::InvalidRules);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn contains(&self, position: &Vector2) -> bool {
+        match self {
+            ArenaShape::Rectangle {
+                half_width,
+                half_height,
+            } => position.x.abs() <= *half_width && position.y.abs() <= *half_height,
+            ArenaShape::Circle { radius } => position.sqr_magnitude() <= radius * radius,
+            ArenaShape::Polygon { points } => {
+                // Count how many edges a ray going right from the position crosses
+                let mut inside = false;
+                for i in 0..points.len() {
+                    let a = points[i];
+                    let b = points[(i + 1) % points.len()];
+                    if (a.y > position.y) != (b.y > position.y) {
+                        let crossing_x = a.x + (position.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                        if position.x < crossing_x {
+                            inside = !inside;
+                        }
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    pub fn centre(&self) -> Vector2 {
+        match self {
+            ArenaShape::Polygon { points } if !points.is_empty() => points
+                .iter()
+                .fold(Vector2 { x: 0.0, y: 0.0 }, |sum, p| sum.add(p))
+                .scale(1.0 / points.len() as f32),
+            _ => Vector2 { x: 0.0, y: 0.0 },
+        }
+    }
+
+    // Same shape shrunk or grown around its centre
+    pub fn scaled(&self, factor: f32) -> ArenaShape {
+        match self {
+            ArenaShape::Rectangle {
+                half_width,
+                half_height,
+            } => ArenaShape::Rectangle {
+                half_width: half_width * factor,
+                half_height: half_height * factor,
+            },
+            ArenaShape::Circle { radius } => ArenaShape::Circle {
+                radius: radius * factor,
+            },
+            ArenaShape::Polygon { points } => {
+                let centre = self.centre();
+                ArenaShape::Polygon {
+                    points: points
+                        .iter()
+                        .map(|p| centre.add(&p.subtract(&centre).scale(factor)))
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    // Point on the edge of the arena closest to the position
+    pub fn closest_edge_point(&self, position: &Vector2) -> Vector2 {
+        match self {
+            ArenaShape::Rectangle {
+                half_width,
+                half_height,
+            } => Vector2 {
+                x: position.x.clamp(-half_width, *half_width),
+                y: position.y.clamp(-half_height, *half_height),
+            },
+            ArenaShape::Circle { radius } => position.normalized().scale(*radius),
+            ArenaShape::Polygon { points } => {
+                let mut closest = *position;
+                let mut closest_distance = f32::MAX;
+                for i in 0..points.len() {
+                    let point =
+                        position.closest_on_segment(&points[i], &points[(i + 1) % points.len()]);
+                    let distance = position.subtract(&point).sqr_magnitude();
+                    if distance < closest_distance {
+                        closest = point;
+                        closest_distance = distance;
+                    }
+                }
+                closest
+            }
+        }
+    }
+
+    // Mirror an outside position and heading back in across the nearest edge
+    pub fn bounce(&self, position: &Vector2, rotation: f32) -> (Vector2, f32) {
+        let edge = self.closest_edge_point(position);
+        let overshoot = position.subtract(&edge);
+        let normal = overshoot.normalized();
+
+        let forward = Vector2 {
+            x: rotation.to_radians().cos(),
+            y: rotation.to_radians().sin(),
+        };
+        let reflected = forward.subtract(&normal.scale(2.0 * forward.dot_product(&normal)));
+        let rotation = reflected.y.atan2(reflected.x).to_degrees();
+
+        // Overshooting by more than the arena is wide leaves the ship on the edge
+        let mirrored = edge.subtract(&overshoot);
+        if self.contains(&mirrored) {
+            (mirrored, rotation)
+        } else {
+            (edge, rotation)
+        }
+    }
+
+    // Bring an outside position back in on the opposite side
+    pub fn wrap(&self, position: &Vector2) -> Vector2 {
+        match self {
+            ArenaShape::Rectangle {
+                half_width,
+                half_height,
+            } => Vector2 {
+                x: (position.x + half_width).rem_euclid(half_width * 2.0) - half_width,
+                y: (position.y + half_height).rem_euclid(half_height * 2.0) - half_height,
+            },
+            _ => {
+                // Re-enter through the edge point opposite the centre, keeping the overshoot
+                let edge = self.closest_edge_point(position);
+                let overshoot = position.subtract(&edge).magnitude();
+                let centre = self.centre();
+                let opposite = centre.scale(2.0).subtract(&edge);
+                let inward = centre.subtract(&opposite).normalized();
+                let wrapped = opposite.add(&inward.scale(overshoot));
+                if self.contains(&wrapped) {
+                    wrapped
+                } else {
+                    self.closest_edge_point(&wrapped)
+                }
+            }
+        }
+    }
+}
+
+impl GameSession {
+    // Apply the out of bounds rule to every ship that ended its movement outside the arena
+    pub fn enforce_arena(&mut self) {
+        for i in 0..self.ships.len() {
+            let ship = &self.ships[i];
+            if ship.health <= 0.0 {
+                continue;
+            }
+
+            let position = ship.position();
+            if self.rules.arena.contains(&position) {
+                continue;
+            }
+
+            msg!("Ship {} is out of bounds", ship.id);
+            match self.rules.out_of_bounds_rule {
+                OutOfBoundsRule::Destroy => self.ships[i].health = 0.0,
+                OutOfBoundsRule::DamagePerTurn => {
+                    self.ships[i].health -= self.rules.out_of_bounds_damage
+                }
+                OutOfBoundsRule::Bounce => {
+                    let (position, rotation) = self.rules.arena.bounce(&position, ship.rotation);
+                    let position = self.nearest_free_spot(i, &position);
+                    self.ships[i].set_pose(&position, rotation);
+                }
+                OutOfBoundsRule::Wrap => {
+                    let rotation = ship.rotation;
+                    let position = self.rules.arena.wrap(&position);
+                    let position = self.nearest_free_spot(i, &position);
+                    self.ships[i].set_pose(&position, rotation);
+                }
+            }
+        }
+    }
+
+    // Closest spot inside the arena that doesn't touch any other ship, so a ship that bounces or
+    // wraps can't land inside another one and get stuck ramming it every turn
+    pub fn nearest_free_spot(&self, ship_index: usize, position: &Vector2) -> Vector2 {
+        let contact = SHIP_RADIUS * 2.0;
+        let is_free = |spot: &Vector2| {
+            self.rules.arena.contains(spot)
+                && self.ships.iter().enumerate().all(|(i, other)| {
+                    i == ship_index
+                        || other.health <= 0.0
+                        || other.position().subtract(spot).sqr_magnitude() >= contact * contact
+                })
+        };
+
+        if is_free(position) {
+            return *position;
+        }
+
+        // Try rings of spots around the position, each ring a bit further out
+        for ring in 1..=FREE_SPOT_RINGS {
+            let distance = ring as f32 * SHIP_RADIUS;
+            for direction in 0..FREE_SPOT_DIRECTIONS {
+                let angle = (direction as f32 * 360.0 / FREE_SPOT_DIRECTIONS as f32).to_radians();
+                let spot = position.add(&Vector2 {
+                    x: angle.cos() * distance,
+                    y: angle.sin() * distance,
+                });
+                if is_free(&spot) {
+                    return spot;
+                }
+            }
+        }
+
+        *position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::ship_class::ShipClass;
+    use crate::gameplay::test_session::*;
+
+    fn rules_with(out_of_bounds_rule: OutOfBoundsRule) -> SessionRules {
+        SessionRules {
+            out_of_bounds_rule,
+            ..SessionRules::default()
+        }
+    }
+
+    // Player 1's ship flies 20 straight towards the bottom edge at y = -73 and ends up 12 past it
+    fn fly_out(out_of_bounds_rule: OutOfBoundsRule) -> GameSession {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship],
+            rules_with(out_of_bounds_rule),
+        );
+        place(&mut session, 0, 0.0, -65.0, -90.0);
+        plan(&session, &[(0, straight(20.0)), (1, straight(10.0))])
+    }
+
+    fn polygon(points: &[(f32, f32)]) -> ArenaShape {
+        ArenaShape::Polygon {
+            points: points
+                .iter()
+                .map(|(x, y)| Vector2 { x: *x, y: *y })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn ships_outside_are_destroyed_by_default() {
+        let session = fly_out(OutOfBoundsRule::Destroy);
+        assert_eq!(session.ships[0].health, 0.0);
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER2);
+    }
+
+    #[test]
+    fn bounce_mirrors_the_ship_back_in() {
+        let session = fly_out(OutOfBoundsRule::Bounce);
+        let ship = &session.ships[0];
+        assert!((ship.y_position + 61.0).abs() < 0.01);
+        assert!((ship.rotation - 90.0).abs() < 0.01);
+        assert_eq!(ship.health, 100.0);
+    }
+
+    #[test]
+    fn wrap_brings_the_ship_in_on_the_other_side() {
+        let session = fly_out(OutOfBoundsRule::Wrap);
+        let ship = &session.ships[0];
+        assert!((ship.y_position - 61.0).abs() < 0.01);
+        assert!((ship.rotation + 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn bounced_ship_is_moved_clear_of_other_ships() {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship],
+            rules_with(OutOfBoundsRule::Bounce),
+        );
+        place(&mut session, 0, 0.0, -65.0, -90.0);
+        // Ends up 3 to the side of where ship 0 bounces back to
+        place(&mut session, 1, 3.0, -51.0, -90.0);
+
+        let session = plan(&session, &[(0, straight(20.0)), (1, straight(10.0))]);
+        let bounced = session.ships[0].position();
+        let other = session.ships[1].position();
+        assert!(bounced.subtract(&other).magnitude() >= SHIP_RADIUS * 2.0);
+        assert!(session.rules.arena.contains(&bounced));
+        assert!(session.last_action_data.is_empty());
+    }
+
+    #[test]
+    fn arena_needs_a_size() {
+        assert!(default_arena().validate().is_ok());
+        assert!(ArenaShape::Circle { radius: 0.0 }.validate().is_err());
+        assert!(ArenaShape::Rectangle {
+            half_width: f32::NAN,
+            half_height: 73.0,
+        }
+        .validate()
+        .is_err());
+        assert!(polygon(&[(-50.0, -70.0), (50.0, 70.0)]).validate().is_err());
+    }
+
+    #[test]
+    fn arena_has_to_contain_both_deployment_zones() {
+        assert!(ArenaShape::Circle { radius: 50.0 }.validate().is_err());
+        assert!(ArenaShape::Circle { radius: 75.0 }.validate().is_ok());
+        assert!(polygon(&[(-50.0, -70.0), (50.0, -70.0), (0.0, 70.0)])
+            .validate()
+            .is_err());
+
+        let hexagon = [
+            (-40.0, -70.0),
+            (40.0, -70.0),
+            (50.0, 0.0),
+            (40.0, 70.0),
+            (-40.0, 70.0),
+            (-50.0, 0.0),
+        ];
+        assert!(polygon(&hexagon).validate().is_ok());
+
+        // Every zone corner is inside, but a notch cuts into player 1's zone
+        let notched = [
+            (-40.0, -70.0),
+            (-1.0, -70.0),
+            (0.0, -40.0),
+            (1.0, -70.0),
+            (40.0, -70.0),
+            (40.0, 70.0),
+            (-40.0, 70.0),
+        ];
+        assert!(polygon(&notched).validate().is_err());
+    }
+
+    #[test]
+    fn session_rules_check_the_arena() {
+        let mut rules = SessionRules::default();
+        assert!(rules.validate().is_ok());
+
+        rules.arena = polygon(&[(-50.0, -70.0), (50.0, -70.0)]);
+        assert!(matches!(rules.validate(), Err(GameError::InvalidRules)));
+    }
+}
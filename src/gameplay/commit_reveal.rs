@@ -0,0 +1,78 @@
+use borsh::BorshSerialize;
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::seat::Seat;
+use crate::utils::commitment;
+use crate::utils::random;
+use solana_program::msg;
+
+// Bookkeeping shared by every phase where both players commit to a hidden choice and reveal it later
+impl GameSession {
+    // Store the player's commitment and mark them ready, returns whether both players have committed
+    pub fn record_commitment(
+        &mut self,
+        seat: Seat,
+        commitment: [u8; 32],
+    ) -> Result<bool, GameError> {
+        if let Some((player1_ready, player2_ready)) = self.phase.player_flags_mut() {
+            let ready = match seat {
+                Seat::Player1 => player1_ready,
+                Seat::Player2 => player2_ready,
+            };
+
+            if *ready {
+                msg!("You already committed this phase");
+                return Err(GameError::AlreadyCommitted);
+            }
+
+            *ready = true;
+        }
+
+        // Only the hash is stored, so the other player can't see our choice yet
+        match seat {
+            Seat::Player1 => self.player1_commitment = commitment,
+            Seat::Player2 => self.player2_commitment = commitment,
+        }
+
+        Ok(self.phase.player_flags() == Some((true, true)))
+    }
+
+    // Check the revealed data against the player's commitment and mark them revealed, returns whether
+    // both players have revealed. The data is hashed as borsh, so maps have to be sorted by key first
+    pub fn record_reveal<T: BorshSerialize>(
+        &mut self,
+        seat: Seat,
+        data: &T,
+        salt: &[u8; 32],
+    ) -> Result<bool, GameError> {
+        let commitment = match seat {
+            Seat::Player1 => self.player1_commitment,
+            Seat::Player2 => self.player2_commitment,
+        };
+
+        let data_bytes = data.try_to_vec().map_err(|_| GameError::InvalidReveal)?;
+        if !commitment::verify(&commitment, &data_bytes, salt) {
+            msg!("Reveal doesn't match the commitment");
+            return Err(GameError::InvalidReveal);
+        }
+
+        // The salt doubles as this player's contribution to the game's randomness
+        self.entropy = random::mix_entropy(&self.entropy, salt);
+
+        if let Some((player1_revealed, player2_revealed)) = self.phase.player_flags_mut() {
+            let revealed = match seat {
+                Seat::Player1 => player1_revealed,
+                Seat::Player2 => player2_revealed,
+            };
+
+            if *revealed {
+                msg!("You already revealed this phase");
+                return Err(GameError::AlreadyRevealed);
+            }
+
+            *revealed = true;
+        }
+
+        Ok(self.phase.player_flags() == Some((true, true)))
+    }
+}
@@ -0,0 +1,194 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::gameplay::ship::{Ship, WeaponData};
+
+// How much of its top speed, turning, and weapon arc and damage a ship keeps with that system damaged
+pub const DAMAGED_ENGINES_SPEED: f32 = 0.6;
+pub const DAMAGED_RUDDER_ANGLE: f32 = 0.5;
+pub const DAMAGED_WEAPONS_ARC: f32 = 0.7;
+pub const DAMAGED_WEAPONS_DAMAGE: f32 = 0.7;
+
+// Parts of a ship a critical hit can knock out
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ShipSystem {
+    // Lowers max speed
+    Engines,
+    // Lowers max turning angle
+    Rudder,
+    // Lowers the arc and damage of every weapon
+    Weapons,
+    // The ship can't upgrade for the rest of the turn
+    Crew,
+}
+
+impl ShipSystem {
+    // Pick a system from a roll between 0 and 1, every system is equally likely
+    pub fn from_roll(value: f64) -> ShipSystem {
+        match (value * 4.0) as u32 {
+            0 => ShipSystem::Engines,
+            1 => ShipSystem::Rudder,
+            2 => ShipSystem::Weapons,
+            _ => ShipSystem::Crew,
+        }
+    }
+}
+
+// Systems that are currently damaged, everything but the crew stays broken until repaired
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DamagedSystems {
+    pub engines: bool,
+    pub rudder: bool,
+    pub weapons: bool,
+    pub crew: bool,
+}
+
+impl DamagedSystems {
+    pub fn none() -> Self {
+        DamagedSystems {
+            engines: false,
+            rudder: false,
+            weapons: false,
+            crew: false,
+        }
+    }
+}
+
+impl Ship {
+    pub fn damage_system(&mut self, system: ShipSystem) {
+        match system {
+            ShipSystem::Engines => self.damaged_systems.engines = true,
+            ShipSystem::Rudder => self.damaged_systems.rudder = true,
+            ShipSystem::Weapons => self.damaged_systems.weapons = true,
+            ShipSystem::Crew => self.damaged_systems.crew = true,
+        }
+    }
+
+    pub fn effective_max_speed(&self) -> f32 {
+        if self.damaged_systems.engines {
+            (self.max_speed * DAMAGED_ENGINES_SPEED).max(self.min_speed)
+        } else {
+            self.max_speed
+        }
+    }
+
+    pub fn effective_max_angle(&self) -> f32 {
+        if self.damaged_systems.rudder {
+            (self.max_angle * DAMAGED_RUDDER_ANGLE).max(self.min_angle)
+        } else {
+            self.max_angle
+        }
+    }
+
+    pub fn effective_arc(&self, weapon: &WeaponData) -> f32 {
+        if self.damaged_systems.weapons {
+            weapon.arc * DAMAGED_WEAPONS_ARC
+        } else {
+            weapon.arc
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GameError;
+    use crate::gameplay::game_session::GameSession;
+    use crate::gameplay::rules::{ActionResult, GameAction};
+    use crate::gameplay::shot::{ModifierSource, ShotBreakdown};
+    use crate::gameplay::test_session::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    fn fire(session: &GameSession) -> ShotBreakdown {
+        let (_, result) = session
+            .apply(
+                &context(&PLAYER1, session.updated_at),
+                GameAction::FireWeapon {
+                    ship_id: 0,
+                    mount_index: 0,
+                    target_id: 1,
+                },
+            )
+            .unwrap();
+
+        match result {
+            ActionResult::WeaponFired { shot, .. } => shot,
+            _ => panic!("Expected a shot"),
+        }
+    }
+
+    #[test]
+    fn damaged_systems_lower_the_ship_stats() {
+        let mut ship = duel_in_action().ships[0].clone();
+        let bow_cannon = ship.weapons[0];
+
+        ship.damage_system(ShipSystem::Engines);
+        assert!(close(ship.effective_max_speed(), 12.0));
+        ship.damage_system(ShipSystem::Rudder);
+        assert!(close(ship.effective_max_angle(), 45.0));
+        ship.damage_system(ShipSystem::Weapons);
+        assert!(close(ship.effective_arc(&bow_cannon), 31.5));
+
+        // Never slower than the minimum speed the ship has to fly
+        ship.max_speed = 15.0;
+        assert_eq!(ship.effective_max_speed(), ship.min_speed);
+    }
+
+    #[test]
+    fn damaged_weapons_hit_for_less() {
+        let mut session = duel_in_action();
+        let intact = fire(&session);
+
+        session.ships[0].damage_system(ShipSystem::Weapons);
+        let damaged = fire(&session);
+        assert!(damaged
+            .modifiers
+            .iter()
+            .any(|m| m.source == ModifierSource::DamagedWeapons
+                && m.damage == DAMAGED_WEAPONS_DAMAGE));
+        assert!(close(
+            damaged.damage,
+            intact.damage * DAMAGED_WEAPONS_DAMAGE
+        ));
+        assert_eq!(damaged.hit_chance, intact.hit_chance);
+    }
+
+    #[test]
+    fn injured_crew_cant_upgrade_until_the_next_turn() {
+        let mut session = duel_in_action();
+        session.ships[0].damage_system(ShipSystem::Crew);
+        let upgrade = || GameAction::Upgrade {
+            ship_id: 0,
+            upgrade_id: 1,
+        };
+
+        let result = apply(&session, &PLAYER1, upgrade());
+        assert!(matches!(result, Err(GameError::CrewInjured)));
+
+        let session = apply(&session, &PLAYER1, GameAction::Skip { ship_id: 0 }).unwrap();
+        let session = apply(&session, &PLAYER2, GameAction::Skip { ship_id: 1 }).unwrap();
+        assert!(!session.ships[0].damaged_systems.crew);
+
+        let session = plan(&session, &[(0, straight(10.0)), (1, straight(10.0))]);
+        assert!(apply(&session, &PLAYER1, upgrade()).is_ok());
+    }
+
+    #[test]
+    fn repairs_fix_every_damaged_system() {
+        let mut session = duel_in_action();
+        session.ships[0].health = 50.0;
+        for system in [
+            ShipSystem::Engines,
+            ShipSystem::Rudder,
+            ShipSystem::Weapons,
+            ShipSystem::Crew,
+        ] {
+            session.ships[0].damage_system(system);
+        }
+
+        let session = apply(&session, &PLAYER1, GameAction::Repair { ship_id: 0 }).unwrap();
+        assert_eq!(session.ships[0].damaged_systems, DamagedSystems::none());
+        assert_eq!(session.ships[0].health, 75.0);
+    }
+}
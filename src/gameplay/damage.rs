@@ -0,0 +1,48 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::gameplay::ship::Ship;
+
+// How a weapon's damage gets through shields and armor
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DamageType {
+    // Stopped by shields first, whatever gets through is reduced by armor
+    Cannon,
+    // Cuts straight through shields, but still has to get past the armor
+    Axe,
+    // Stopped by shields, but ignores armor
+    Harpoon,
+}
+
+// Where the damage of a single hit ended up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HitDamage {
+    pub shield_damage: f32,
+    pub hull_damage: f32,
+}
+
+impl Ship {
+    pub fn take_hit(&mut self, damage: f32, damage_type: DamageType) -> HitDamage {
+        let damage = damage.max(0.0);
+
+        let shield_damage = match damage_type {
+            DamageType::Axe => 0.0,
+            DamageType::Cannon | DamageType::Harpoon => damage.min(self.shields.max(0.0)),
+        };
+        self.shields -= shield_damage;
+
+        let remaining = damage - shield_damage;
+        let hull_damage = match damage_type {
+            DamageType::Harpoon => remaining,
+            DamageType::Cannon | DamageType::Axe => (remaining - self.armor).max(0.0),
+        };
+        self.health -= hull_damage;
+
+        HitDamage {
+            shield_damage,
+            hull_damage,
+        }
+    }
+
+    pub fn regenerate_shields(&mut self) {
+        self.shields = (self.shields + self.shield_regen).min(self.max_shields);
+    }
+}
@@ -0,0 +1,424 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::phase::GamePhase;
+use crate::gameplay::seat::Seat;
+use crate::gameplay::session_rules::InvalidPlacementRule;
+use crate::gameplay::ship::SHIP_RADIUS;
+use crate::utils::dictionary::Dictionary;
+use crate::utils::vec2::Vector2;
+use solana_program::msg;
+
+// Degrees a ship can be turned away from its zone's facing when placed
+pub const MAX_DEPLOYMENT_TURN: f32 = 90.0;
+
+// Where a player wants one of their ships to start
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Placement {
+    pub x_position: f32,
+    pub y_position: f32,
+    pub rotation: f32,
+}
+
+impl Placement {
+    pub fn position(&self) -> Vector2 {
+        Vector2 {
+            x: self.x_position,
+            y: self.y_position,
+        }
+    }
+}
+
+// Area a player can place their ships in, facing roughly towards the enemy
+pub struct DeploymentZone {
+    pub min: Vector2,
+    pub max: Vector2,
+    pub facing: f32,
+}
+
+impl DeploymentZone {
+    pub fn contains(&self, position: &Vector2) -> bool {
+        position.x >= self.min.x
+            && position.x <= self.max.x
+            && position.y >= self.min.y
+            && position.y <= self.max.y
+    }
+
+    pub fn corners(&self) -> [Vector2; 4] {
+        [
+            self.min,
+            Vector2 {
+                x: self.max.x,
+                y: self.min.y,
+            },
+            self.max,
+            Vector2 {
+                x: self.min.x,
+                y: self.max.y,
+            },
+        ]
+    }
+
+    pub fn allows_rotation(&self, rotation: f32) -> bool {
+        let difference = (rotation - self.facing + 180.0).rem_euclid(360.0) - 180.0;
+        difference.abs() <= MAX_DEPLOYMENT_TURN
+    }
+}
+
+// Zones mirror each other through the centre of the arena
+pub fn deployment_zone(seat: Seat) -> DeploymentZone {
+    match seat {
+        Seat::Player1 => DeploymentZone {
+            min: Vector2 { x: -35.0, y: -60.0 },
+            max: Vector2 { x: 35.0, y: -25.0 },
+            facing: 90.0,
+        },
+        Seat::Player2 => DeploymentZone {
+            min: Vector2 { x: -35.0, y: 25.0 },
+            max: Vector2 { x: 35.0, y: 60.0 },
+            facing: -90.0,
+        },
+    }
+}
+
+impl GameSession {
+    pub fn commit_deployment(
+        &mut self,
+        seat: Seat,
+        commitment: [u8; 32],
+    ) -> Result<bool, GameError> {
+        if !self.record_commitment(seat, commitment)? {
+            return Ok(false);
+        }
+
+        self.transition(GamePhase::deployment_reveal())?;
+        Ok(true)
+    }
+
+    pub fn reveal_deployment(
+        &mut self,
+        seat: Seat,
+        placements: &Dictionary<u32, Placement>,
+        salt: &[u8; 32],
+    ) -> Result<bool, GameError> {
+        // Placements are hashed as a borsh Vec<(u32, Placement)> ordered by ship id
+        let all_revealed = self.record_reveal(seat, &placements.as_sorted_vec(), salt)?;
+
+        // Ships without a valid placement keep their spot in the starting line
+        let mut moves = Vec::new();
+        for (ship_id, placement) in placements.as_sorted_vec() {
+            if self.is_valid_placement(seat, &placement) {
+                moves.push((ship_id, placement));
+            } else {
+                self.reject_placement(ship_id)?;
+            }
+        }
+
+        // Overlaps are checked against the layout after deployment, so ships can swap spots. Every
+        // dropped placement leaves its ship in the starting line, which can clash with another one
+        while let Some(index) = moves
+            .iter()
+            .position(|(ship_id, placement)| self.overlaps_layout(*ship_id, placement, &moves))
+        {
+            let (ship_id, _) = moves.remove(index);
+            self.reject_placement(ship_id)?;
+        }
+
+        for (ship_id, placement) in moves {
+            self.ships[ship_id as usize].set_pose(&placement.position(), placement.rotation);
+        }
+
+        if all_revealed {
+            self.finish_deployment()?;
+        }
+
+        Ok(all_revealed)
+    }
+
+    // Inside our zone and the arena, facing the right way and clear of obstacles
+    fn is_valid_placement(&self, seat: Seat, placement: &Placement) -> bool {
+        if !placement.x_position.is_finite()
+            || !placement.y_position.is_finite()
+            || !placement.rotation.is_finite()
+        {
+            return false;
+        }
+
+        let position = placement.position();
+        let zone = deployment_zone(seat);
+        if !zone.contains(&position)
+            || !zone.allows_rotation(placement.rotation)
+            || !self.rules.arena.contains(&position)
+        {
+            return false;
+        }
+
+        !self
+            .obstacles
+            .iter()
+            .any(|o| o.overlaps(&position, SHIP_RADIUS))
+    }
+
+    // Whether a placed ship would touch another ship, where ships that aren't being placed stay put
+    fn overlaps_layout(
+        &self,
+        ship_id: u32,
+        placement: &Placement,
+        moves: &[(u32, Placement)],
+    ) -> bool {
+        let position = placement.position();
+        let contact = SHIP_RADIUS * 2.0;
+
+        self.ships.iter().any(|s| {
+            if s.id == ship_id || s.health <= 0.0 {
+                return false;
+            }
+
+            let other = match moves.iter().find(|(id, _)| *id == s.id) {
+                Some((_, other)) => other.position(),
+                None => s.position(),
+            };
+            other.subtract(&position).sqr_magnitude() < contact * contact
+        })
+    }
+
+    fn reject_placement(&self, ship_id: u32) -> Result<(), GameError> {
+        if self.rules.invalid_placement_rule == InvalidPlacementRule::Reject {
+            msg!("Invalid placement for ship {}", ship_id);
+            return Err(GameError::InvalidPlacement);
+        }
+
+        msg!(
+            "Invalid placement for ship {}, keeping its starting spot",
+            ship_id
+        );
+        Ok(())
+    }
+
+    pub fn finish_deployment(&mut self) -> Result<(), GameError> {
+        self.player1_commitment = [0; 32];
+        self.player2_commitment = [0; 32];
+        self.transition(GamePhase::planning())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::obstacle::Obstacle;
+    use crate::gameplay::rules::GameAction;
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::ship_class::ShipClass;
+    use crate::gameplay::test_session::*;
+
+    fn placements(placed: &[(u32, f32, f32, f32)]) -> Dictionary<u32, Placement> {
+        let mut placements = Dictionary::new();
+        for (ship_id, x, y, rotation) in placed {
+            placements.insert(
+                *ship_id,
+                Placement {
+                    x_position: *x,
+                    y_position: *y,
+                    rotation: *rotation,
+                },
+            );
+        }
+        placements
+    }
+
+    // Two longships each, player 1's start at x 7.5 and -7.5 on y -30
+    fn two_on_two(rule: InvalidPlacementRule) -> GameSession {
+        new_deployment(
+            &[ShipClass::Longship, ShipClass::Longship],
+            &[ShipClass::Longship, ShipClass::Longship],
+            SessionRules {
+                invalid_placement_rule: rule,
+                ..SessionRules::default()
+            },
+        )
+    }
+
+    fn deploy_player1(session: &GameSession, placed: &[(u32, f32, f32, f32)]) -> GameSession {
+        deploy(session, &placements(placed), &Dictionary::new())
+    }
+
+    fn reveal_player1(
+        session: &GameSession,
+        placed: &[(u32, f32, f32, f32)],
+    ) -> Result<GameSession, GameError> {
+        let placements = placements(placed);
+        let mut session = session.clone();
+        for (player, seat, placements) in [
+            (PLAYER1, Seat::Player1, &placements),
+            (PLAYER2, Seat::Player2, &Dictionary::new()),
+        ] {
+            let commitment = placement_commitment(placements, seat);
+            session = apply(
+                &session,
+                &player,
+                GameAction::CommitDeployment { commitment },
+            )?;
+        }
+
+        apply(
+            &session,
+            &PLAYER1,
+            GameAction::RevealDeployment {
+                placements,
+                salt: salt(Seat::Player1),
+            },
+        )
+    }
+
+    fn pose(session: &GameSession, ship_id: usize) -> (f32, f32, f32) {
+        let ship = &session.ships[ship_id];
+        (ship.x_position, ship.y_position, ship.rotation)
+    }
+
+    #[test]
+    fn ships_are_placed_once_both_players_revealed() {
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = deploy_player1(&session, &[(0, 20.0, -50.0, 45.0)]);
+
+        assert_eq!(session.phase, GamePhase::planning());
+        assert_eq!(pose(&session, 0), (20.0, -50.0, 45.0));
+        assert_eq!(pose(&session, 1), (-7.5, -30.0, 90.0));
+    }
+
+    #[test]
+    fn placements_have_to_be_inside_the_zone_and_face_the_enemy() {
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+
+        // Outside the zone, past the edge of it and turned around
+        let session = deploy_player1(&session, &[(0, 0.0, 0.0, 90.0), (1, 0.0, -40.0, 181.0)]);
+        assert_eq!(pose(&session, 0), (7.5, -30.0, 90.0));
+        assert_eq!(pose(&session, 1), (-7.5, -30.0, 90.0));
+
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = deploy_player1(&session, &[(0, 35.0, -60.0, 0.0), (1, -35.0, -25.0, 180.0)]);
+        assert_eq!(pose(&session, 0), (35.0, -60.0, 0.0));
+        assert_eq!(pose(&session, 1), (-35.0, -25.0, 180.0));
+    }
+
+    #[test]
+    fn placements_cant_overlap_ships_or_obstacles() {
+        let mut session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        session.obstacles.push(Obstacle {
+            id: 0,
+            x_position: 20.0,
+            y_position: -50.0,
+            size: 3.0,
+            health: 100,
+        });
+
+        // Ship 1 stays in the starting line, so ship 0 can't be put right next to it
+        let session = deploy_player1(&session, &[(0, 20.0, -50.0, 90.0)]);
+        assert_eq!(pose(&session, 0), (7.5, -30.0, 90.0));
+
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = deploy_player1(&session, &[(0, -6.0, -30.0, 90.0)]);
+        assert_eq!(pose(&session, 0), (7.5, -30.0, 90.0));
+
+        // Both placed on the same spot, the first one gives way
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = deploy_player1(&session, &[(0, 0.0, -50.0, 90.0), (1, 1.0, -50.0, 90.0)]);
+        assert_eq!(pose(&session, 0), (7.5, -30.0, 90.0));
+        assert_eq!(pose(&session, 1), (1.0, -50.0, 90.0));
+    }
+
+    #[test]
+    fn ships_can_swap_spots() {
+        let session = two_on_two(InvalidPlacementRule::Reject);
+        let session = deploy_player1(&session, &[(0, -7.5, -30.0, 90.0), (1, 7.5, -30.0, 90.0)]);
+
+        assert_eq!(pose(&session, 0), (-7.5, -30.0, 90.0));
+        assert_eq!(pose(&session, 1), (7.5, -30.0, 90.0));
+    }
+
+    #[test]
+    fn dropped_placement_can_block_another_one() {
+        // Ship 0 can't go outside the zone, so it stays on the spot ship 1 wanted
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = deploy_player1(&session, &[(0, 0.0, 0.0, 90.0), (1, 7.5, -30.0, 90.0)]);
+
+        assert_eq!(pose(&session, 0), (7.5, -30.0, 90.0));
+        assert_eq!(pose(&session, 1), (-7.5, -30.0, 90.0));
+    }
+
+    #[test]
+    fn reject_rule_fails_the_reveal() {
+        let session = two_on_two(InvalidPlacementRule::Reject);
+
+        let result = reveal_player1(&session, &[(0, 0.0, 0.0, 90.0)]);
+        assert!(matches!(result, Err(GameError::InvalidPlacement)));
+
+        let result = reveal_player1(&session, &[(0, -6.0, -30.0, 90.0)]);
+        assert!(matches!(result, Err(GameError::InvalidPlacement)));
+
+        let session = reveal_player1(&session, &[(0, 0.0, -50.0, 90.0)]).unwrap();
+        assert_eq!(pose(&session, 0), (0.0, -50.0, 90.0));
+    }
+
+    #[test]
+    fn stalling_during_deployment_keeps_the_starting_line() {
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let commitment = placement_commitment(&Dictionary::new(), Seat::Player1);
+        let session = apply(
+            &session,
+            &PLAYER1,
+            GameAction::CommitDeployment { commitment },
+        )
+        .unwrap();
+        let deadline = session.phase_deadline;
+
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert_eq!(session.player2_strikes, 1);
+        assert_eq!(
+            session.phase,
+            GamePhase::DeploymentReveal {
+                player1_revealed: false,
+                player2_revealed: true,
+            }
+        );
+
+        // Nobody deploying at all just starts the game
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let deadline = session.phase_deadline;
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert_eq!(session.phase, GamePhase::planning());
+        assert_eq!(session.player1_strikes, 0);
+        assert_eq!(session.player2_strikes, 0);
+    }
+
+    #[test]
+    fn player_that_never_reveals_their_placements_forfeits() {
+        let session = two_on_two(InvalidPlacementRule::KeepStartingLine);
+        let session = reveal_player1(&session, &[(0, 0.0, -50.0, 90.0)]).unwrap();
+        let deadline = session.phase_deadline;
+
+        let result = apply_at(&session, &OUTSIDER, GameAction::ClaimTimeout {}, deadline);
+        assert!(matches!(result, Err(GameError::DeadlineNotReached)));
+
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER1);
+    }
+}
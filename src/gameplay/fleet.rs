@@ -0,0 +1,112 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::ship_class::ShipClass;
+use crate::gameplay::upgrade;
+use crate::utils::vec2::Vector2;
+use solana_program::{msg, pubkey::Pubkey};
+
+// Distance between ships in the starting line
+const FLEET_SPACING: f32 = 15.0;
+
+// One ship of a fleet, with the upgrades it starts the match with
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ShipSpec {
+    pub class: ShipClass,
+    pub upgrades: Vec<u32>,
+}
+
+impl ShipSpec {
+    // Install the upgrades on a fresh ship to check they are legal, and add up the points
+    pub fn cost(&self) -> Result<u32, GameError> {
+        let mut ship = self
+            .class
+            .build(0, Pubkey::default(), &Vector2 { x: 0.0, y: 0.0 }, 0.0);
+        let mut cost = self.class.cost();
+        for upgrade_id in self.upgrades.iter() {
+            ship.apply_upgrade(*upgrade_id)?;
+            cost += upgrade::find_upgrade(*upgrade_id).map_or(0, |u| u.cost);
+        }
+
+        Ok(cost)
+    }
+}
+
+// Check a fleet against the queue's ship count limits and point budget
+pub fn validate_fleet(
+    fleet: &[ShipSpec],
+    min_ships: u8,
+    max_ships: u8,
+    budget: u32,
+) -> Result<(), GameError> {
+    if fleet.len() < min_ships as usize || fleet.len() > max_ships as usize {
+        msg!(
+            "Fleet has {} ships, it needs between {} and {}",
+            fleet.len(),
+            min_ships,
+            max_ships
+        );
+        return Err(GameError::InvalidFleetSize);
+    }
+
+    let mut total = 0;
+    for spec in fleet.iter() {
+        total += spec.cost()?;
+    }
+
+    if total > budget {
+        msg!("Fleet costs {} points but the budget is {}", total, budget);
+        return Err(GameError::FleetOverBudget);
+    }
+
+    Ok(())
+}
+
+impl GameSession {
+    // Line both fleets up facing each other, player 2's ships mirror player 1's through the centre.
+    // Ships stay here unless they get a placement during deployment
+    pub fn spawn_fleets(
+        &mut self,
+        player1_fleet: &[ShipSpec],
+        player2_fleet: &[ShipSpec],
+    ) -> Result<(), GameError> {
+        self.ships = Vec::new();
+
+        for (i, spec) in player1_fleet.iter().enumerate() {
+            let offset = (player1_fleet.len() - 1) as f32 / 2.0 - i as f32;
+            let position = Vector2 {
+                x: offset * FLEET_SPACING,
+                y: -30.0,
+            };
+            self.spawn_ship(spec, self.player1, &position, 90.0)?;
+        }
+
+        for (i, spec) in player2_fleet.iter().enumerate() {
+            let offset = (player2_fleet.len() - 1) as f32 / 2.0 - i as f32;
+            let position = Vector2 {
+                x: -offset * FLEET_SPACING,
+                y: 30.0,
+            };
+            self.spawn_ship(spec, self.player2, &position, -90.0)?;
+        }
+
+        Ok(())
+    }
+
+    fn spawn_ship(
+        &mut self,
+        spec: &ShipSpec,
+        owner: Pubkey,
+        position: &Vector2,
+        rotation: f32,
+    ) -> Result<(), GameError> {
+        let id = self.ships.len() as u32;
+        let mut ship = spec.class.build(id, owner, position, rotation);
+        for upgrade_id in spec.upgrades.iter() {
+            ship.apply_upgrade(*upgrade_id)?;
+        }
+
+        self.ships.push(ship);
+        Ok(())
+    }
+}
@@ -1,40 +1,120 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use crate::error::GameError;
+use crate::gameplay::obstacle::Obstacle;
+use crate::gameplay::phase::GamePhase;
+use crate::gameplay::rules::ActionContext;
+use crate::gameplay::session_rules::SessionRules;
 use crate::gameplay::ship::Ship;
+use crate::gameplay::shot::ShotBreakdown;
+use crate::utils::dictionary::Dictionary;
+use crate::utils::random::RollRecord;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
     msg,
     program::invoke,
     program::invoke_signed,
     program_error::ProgramError,
+    pubkey,
     pubkey::Pubkey,
     system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{self, clock::Clock, instructions, rent::Rent, Sysvar},
 };
 use std::convert::TryInto;
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub struct GameSession {
     pub bump_seed: u8,
     pub game_id: u64,
+    pub rent_payer: Pubkey,
+    // Lamports each signer added to keep the account rent exempt as it grew, paid back on finalize
+    pub rent_contributions: Dictionary<Pubkey, u64>,
     pub player1: Pubkey,
     pub player2: Pubkey,
 
-    // Flags for use in ending turns, phases etc.
-    pub player1_ready: bool,
-    pub player2_ready: bool,
     pub active_player: Pubkey,
     pub winning_player: Pubkey,
+    pub rules: SessionRules,
+
+    // Hashes of the maneuvers each player committed to this turn
+    pub player1_commitment: [u8; 32],
+    pub player2_commitment: [u8; 32],
+
+    // Randomness, both players add their revealed salts to the entropy every turn
+    pub entropy: [u8; 32],
+    pub roll_nonce: u64,
+    // Every roll of the last action in order, a hit can roll again for a critical
+    pub last_rolls: Vec<RollRecord>,
 
     // This is so the clients can see what action was done
     pub last_action: u32,
     pub last_action_data: Vec<u32>,
+    // How the hit chance and damage of the last shot were worked out
+    pub last_shot: Option<ShotBreakdown>,
+
+    // Timers, updated_at is the time of the last action
+    pub updated_at: i64,
+    pub phase_deadline: i64,
+    pub player1_strikes: u8,
+    pub player2_strikes: u8,
+    pub player1_time_left: i64,
+    pub player2_time_left: i64,
+
+    // Scrap each player has to spend on upgrades
+    pub player1_scrap: u32,
+    pub player2_scrap: u32,
 
     // Game state
     pub turn: u8,
-    pub phase: u8,
+    pub phase: GamePhase,
     pub ships: Vec<Ship>,
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl GameSession {
+    // Fresh session in the lobby, the pda seeds the entropy until the players add to it
+    pub fn new(bump_seed: u8, game_id: u64, rent_payer: Pubkey, entropy: [u8; 32]) -> Self {
+        GameSession {
+            bump_seed,
+            game_id,
+            rent_payer,
+            rent_contributions: Dictionary::new(),
+            player1: Pubkey::default(),
+            player2: Pubkey::default(),
+
+            active_player: Pubkey::default(),
+            winning_player: Pubkey::default(),
+            rules: SessionRules::default(),
+
+            player1_commitment: [0; 32],
+            player2_commitment: [0; 32],
+
+            entropy,
+            roll_nonce: 0,
+            last_rolls: Vec::new(),
+
+            last_action: 0,
+            last_action_data: Vec::new(),
+            last_shot: None,
+
+            updated_at: 0,
+            phase_deadline: i64::MAX,
+            player1_strikes: 0,
+            player2_strikes: 0,
+            player1_time_left: 0,
+            player2_time_left: 0,
+
+            player1_scrap: 0,
+            player2_scrap: 0,
+
+            // New game state
+            turn: 1,
+            phase: GamePhase::Lobby,
+            ships: Vec::new(),
+            obstacles: Vec::new(),
+        }
+    }
 }
 
 // Derive PDA
@@ -103,33 +183,83 @@ pub fn get_or_create_data(
     // Get account data
     let game_session_data = match GameSession::try_from_slice(&game_session_account.data.borrow()) {
         Ok(data) => data,
-        Err(_) => {
-            let new_game_session_data = GameSession {
-                bump_seed: bump_seed,
-                game_id: game_id,
-                player1: Pubkey::default(),
-                player2: Pubkey::default(),
-
-                player1_ready: false,
-                player2_ready: false,
-                active_player: Pubkey::default(),
-                winning_player: Pubkey::default(),
-
-                last_action: 0,
-                last_action_data: Vec::new(),
-
-                // New game state
-                turn: 1,
-                phase: 0,
-                ships: Vec::new(),
-            };
-            new_game_session_data
-        }
+        Err(_) => GameSession::new(bump_seed, game_id, *initializer.key, pda.to_bytes()),
     };
 
     Ok(game_session_data)
 }
 
+// Everything the rules engine needs to know about the transaction
+pub fn get_action_context(initializer: &AccountInfo) -> Result<ActionContext, ProgramError> {
+    let clock = Clock::get()?;
+
+    Ok(ActionContext {
+        player: *initializer.key,
+        unix_timestamp: clock.unix_timestamp,
+        recent_slot_hash: [0; 32],
+    })
+}
+
+// Compute budget instructions only set limits and fees, they can't look at how a roll went
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+// Most recent hash in the SlotHashes sysvar, which is too big to deserialize as a whole
+pub fn get_recent_slot_hash(
+    slot_hashes_account: &AccountInfo,
+    instructions_account: &AccountInfo,
+) -> Result<[u8; 32], ProgramError> {
+    // The slot hash is readable by anyone during execution, so a wrapper program could predict the
+    // roll and only go through with good ones. Rolls have to come straight from a transaction.
+    if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT {
+        msg!("Rolls can't be made through another program");
+        return Err(GameError::NestedInvocation.into());
+    }
+
+    // Any other instruction in the transaction could check the outcome and fail the whole
+    // transaction on a miss, letting the player retry every slot until they hit
+    check_lone_instruction(instructions_account)?;
+
+    if !sysvar::slot_hashes::check_id(slot_hashes_account.key) {
+        msg!(
+            "Expected the SlotHashes sysvar but received {}",
+            slot_hashes_account.key
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Layout is a u64 entry count followed by (slot: u64, hash: [u8; 32]) entries, newest first
+    let data = slot_hashes_account.try_borrow_data()?;
+    let hash = data.get(16..48).ok_or(ProgramError::InvalidAccountData)?;
+
+    Ok(hash.try_into().unwrap())
+}
+
+fn check_lone_instruction(instructions_account: &AccountInfo) -> ProgramResult {
+    let current_index = instructions::load_current_index_checked(instructions_account)?;
+
+    // Layout starts with a u16 instruction count
+    let count = {
+        let data = instructions_account.try_borrow_data()?;
+        let count = data.get(0..2).ok_or(ProgramError::InvalidAccountData)?;
+        u16::from_le_bytes(count.try_into().unwrap())
+    };
+
+    for index in (0..count).filter(|index| *index != current_index) {
+        let instruction =
+            instructions::load_instruction_at_checked(index as usize, instructions_account)?;
+        if instruction.program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            msg!(
+                "Instruction {} of the transaction calls {}",
+                index,
+                instruction.program_id
+            );
+            return Err(GameError::SharedTransaction.into());
+        }
+    }
+
+    Ok(())
+}
+
 pub fn write_data(accounts: &[AccountInfo], game_session_data: &GameSession) -> ProgramResult {
     // Get accounts
     let account_info_iter = &mut accounts.iter();
@@ -137,6 +267,32 @@ pub fn write_data(accounts: &[AccountInfo], game_session_data: &GameSession) ->
     let system_program = next_account_info(account_info_iter)?;
     let game_session_account = next_account_info(account_info_iter)?;
 
+    // Whoever signs pays for any growth, which is recorded so finalize can pay it back. The entry
+    // takes up space itself, so it's added before measuring and dropped again if nothing was paid
+    let mut game_session_data = game_session_data.clone();
+    let had_contribution = game_session_data
+        .rent_contributions
+        .contains_key(initializer.key);
+    if !had_contribution {
+        game_session_data
+            .rent_contributions
+            .insert(*initializer.key, 0);
+    }
+
+    let rent = Rent::get()?;
+    let lamports = **game_session_account.try_borrow_lamports()?;
+    let measured_size = game_session_data.try_to_vec()?.len();
+    let lamports_diff = rent.minimum_balance(measured_size).saturating_sub(lamports);
+    if let Some(contribution) = game_session_data
+        .rent_contributions
+        .get_mut(initializer.key)
+    {
+        *contribution += lamports_diff;
+    }
+    if lamports_diff == 0 && !had_contribution {
+        game_session_data.rent_contributions.remove(initializer.key);
+    }
+
     // Serialize data
     let mut new_serialized_data = vec![];
     game_session_data.serialize(&mut new_serialized_data)?;
@@ -144,28 +300,19 @@ pub fn write_data(accounts: &[AccountInfo], game_session_data: &GameSession) ->
     let cur_size = game_session_account.data.borrow().len();
     let new_size = new_serialized_data.len();
 
+    if lamports_diff > 0 {
+        invoke(
+            &system_instruction::transfer(initializer.key, game_session_account.key, lamports_diff),
+            &[
+                initializer.clone(),
+                game_session_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
     // Check if account needs to be resized
     if cur_size != new_size {
-        let rent = Rent::get()?;
-        let new_minimum_balance = rent.minimum_balance(new_size);
-
-        let lamports = **game_session_account.try_borrow_lamports()?;
-        let lamports_diff = new_minimum_balance.saturating_sub(lamports);
-        if lamports_diff > 0 {
-            invoke(
-                &system_instruction::transfer(
-                    initializer.key,
-                    game_session_account.key,
-                    lamports_diff,
-                ),
-                &[
-                    initializer.clone(),
-                    game_session_account.clone(),
-                    system_program.clone(),
-                ],
-            )?;
-        }
-
         game_session_account.realloc(new_size, false)?;
     }
 
@@ -176,15 +323,24 @@ pub fn write_data(accounts: &[AccountInfo], game_session_data: &GameSession) ->
     Ok(())
 }
 
-pub fn close_pda(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> ProgramResult {
+// Close the session account, paying back what every signer added as it grew. The rest of the rent
+// goes to the receiver, the accounts of the contributors follow the session account
+pub fn close_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_id: u64,
+    rent_contributions: &Dictionary<Pubkey, u64>,
+) -> ProgramResult {
     // Get accounts
     let account_info_iter = &mut accounts.iter();
-    let initializer = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    let receiver = next_account_info(account_info_iter)?;
+    let _system_program = next_account_info(account_info_iter)?;
     let game_session_account = next_account_info(account_info_iter)?;
+    let contributor_accounts: Vec<&AccountInfo> =
+        std::iter::once(receiver).chain(account_info_iter).collect();
 
     // Provided pda should be the same as the derived pda
-    let (pda, bump_seed) = get_pda(program_id, game_id);
+    let (pda, _bump_seed) = get_pda(program_id, game_id);
     if pda != *game_session_account.key {
         msg!(
             "Invalid PDA for game_id {}, expected {} but received {}",
@@ -195,34 +351,34 @@ pub fn close_pda(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) ->
         return Err(GameError::InvalidPDA.into());
     }
 
-    // Check if account has already been initialized
-    let mut lamports = 0;
-    {
-        lamports = **game_session_account.try_borrow_lamports()?;
+    // The session account is owned by this program, so its lamports can be moved directly
+    let mut lamports = game_session_account.lamports();
+    for (payer, contribution) in rent_contributions.iter() {
+        let payer_account = match contributor_accounts.iter().find(|a| a.key == payer) {
+            Some(account) => account,
+            None => {
+                msg!("Missing the account of rent contributor {}", payer);
+                return Err(GameError::InvalidRentPayer.into());
+            }
+        };
+
+        let refund = (*contribution).min(lamports);
+        **payer_account.try_borrow_mut_lamports()? = payer_account
+            .lamports()
+            .checked_add(refund)
+            .ok_or(ProgramError::InvalidArgument)?;
+        lamports -= refund;
     }
 
-    if lamports > 0 {
-        // Purge all data
-        {
-            let data_field = &mut game_session_account.data.borrow_mut();
-            data_field.fill(0u8);
-        }
+    **receiver.try_borrow_mut_lamports()? = receiver
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **game_session_account.try_borrow_mut_lamports()? = 0;
 
-        // Remove all funds from session account
-        invoke_signed(
-            &system_instruction::transfer(&game_session_account.key, &initializer.key, lamports),
-            &[
-                game_session_account.clone(),
-                initializer.clone(),
-                system_program.clone(),
-            ],
-            &[&[
-                program_id.to_bytes().as_ref(),
-                game_id.to_be_bytes().as_ref(),
-                &[bump_seed],
-            ]],
-        )?;
-    }
+    // Purge all data
+    game_session_account.data.borrow_mut().fill(0u8);
+    game_session_account.realloc(0, false)?;
 
     Ok(())
 }
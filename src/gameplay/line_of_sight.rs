@@ -0,0 +1,165 @@
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::ship::SHIP_RADIUS;
+use crate::utils::vec2::Vector2;
+
+// Rays cast from the shooter to points spread across the target's width
+const SIGHT_RAYS: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineOfSight {
+    pub blocked_rays: u32,
+    pub total_rays: u32,
+}
+
+impl LineOfSight {
+    pub fn is_blocked(&self) -> bool {
+        self.blocked_rays >= self.total_rays
+    }
+
+    // Fraction of the target that is hidden, 0 is fully visible
+    pub fn cover(&self) -> f32 {
+        self.blocked_rays as f32 / self.total_rays as f32
+    }
+
+    // Multiplier for the hit chance, the more of the target is hidden the harder it is to hit
+    pub fn cover_modifier(&self) -> f32 {
+        1.0 - self.cover()
+    }
+}
+
+// What the line of sight is traced to, so it doesn't block itself
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SightTarget {
+    Ship(u32),
+    Obstacle(u32),
+}
+
+impl GameSession {
+    // Trace rays from the shooter across the target and count how many are blocked by obstacles or other ships
+    pub fn line_of_sight(
+        &self,
+        shooter_id: u32,
+        target: SightTarget,
+        target_pos: &Vector2,
+        target_radius: f32,
+    ) -> LineOfSight {
+        let shooter_pos = self.ships[shooter_id as usize].position();
+        let direction = target_pos.subtract(&shooter_pos).normalized();
+        let across = Vector2 {
+            x: -direction.y,
+            y: direction.x,
+        };
+
+        let mut blocked_rays = 0;
+        for i in 0..SIGHT_RAYS {
+            // Spread evenly from one edge of the target to the other
+            let offset = (i as f32 / (SIGHT_RAYS - 1) as f32) * 2.0 - 1.0;
+            let ray_end = target_pos.add(&across.scale(offset * target_radius));
+
+            let blocked_by_obstacle = self
+                .obstacles
+                .iter()
+                .filter(|o| target != SightTarget::Obstacle(o.id))
+                .any(|o| o.blocks_line(&shooter_pos, &ray_end));
+
+            let blocked_by_ship = self
+                .ships
+                .iter()
+                .filter(|s| s.id != shooter_id && target != SightTarget::Ship(s.id))
+                .filter(|s| s.health > 0.0)
+                .any(|s| s.position().distance_to_segment(&shooter_pos, &ray_end) < SHIP_RADIUS);
+
+            if blocked_by_obstacle || blocked_by_ship {
+                blocked_rays += 1;
+            }
+        }
+
+        LineOfSight {
+            blocked_rays,
+            total_rays: SIGHT_RAYS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GameError;
+    use crate::gameplay::game_session::GameSession;
+    use crate::gameplay::obstacle::Obstacle;
+    use crate::gameplay::rules::GameAction;
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::ship_class::ShipClass;
+    use crate::gameplay::test_session::*;
+
+    fn rock(x: f32, y: f32, size: f32) -> Obstacle {
+        Obstacle {
+            id: 0,
+            x_position: x,
+            y_position: y,
+            size,
+            health: 100,
+        }
+    }
+
+    fn sight_to_ship_1(session: &GameSession) -> LineOfSight {
+        let target_pos = session.ships[1].position();
+        session.line_of_sight(0, SightTarget::Ship(1), &target_pos, SHIP_RADIUS)
+    }
+
+    #[test]
+    fn open_water_gives_a_clear_line() {
+        let session = duel_in_action();
+        let sight = sight_to_ship_1(&session);
+        assert_eq!(sight.blocked_rays, 0);
+        assert_eq!(sight.cover_modifier(), 1.0);
+    }
+
+    #[test]
+    fn obstacle_off_to_the_side_gives_partial_cover() {
+        let mut session = duel_in_action();
+        session.obstacles.push(rock(1.5, 0.0, 1.0));
+
+        let sight = sight_to_ship_1(&session);
+        assert!(sight.blocked_rays > 0);
+        assert!(!sight.is_blocked());
+    }
+
+    #[test]
+    fn obstacle_in_between_blocks_the_shot() {
+        let mut session = duel_in_action();
+        session.obstacles.push(rock(0.0, 0.0, 6.0));
+        assert!(sight_to_ship_1(&session).is_blocked());
+
+        let result = apply(
+            &session,
+            &PLAYER1,
+            GameAction::FireWeapon {
+                ship_id: 0,
+                mount_index: 0,
+                target_id: 1,
+            },
+        );
+        assert!(matches!(result, Err(GameError::NoLineOfSight)));
+    }
+
+    #[test]
+    fn ships_block_sight_but_wrecks_dont() {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship, ShipClass::Longship],
+            SessionRules::default(),
+        );
+        place(&mut session, 0, 0.0, -10.0, 90.0);
+        place(&mut session, 1, 0.0, 0.0, -90.0);
+        place(&mut session, 2, 0.0, 10.0, -90.0);
+
+        let target_pos = session.ships[2].position();
+        let sight = session.line_of_sight(0, SightTarget::Ship(2), &target_pos, SHIP_RADIUS);
+        assert!(sight.blocked_rays > 0);
+
+        session.ships[1].health = 0.0;
+        let sight = session.line_of_sight(0, SightTarget::Ship(2), &target_pos, SHIP_RADIUS);
+        assert_eq!(sight.blocked_rays, 0);
+    }
+}
@@ -1,5 +1,7 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use crate::error::GameError;
+use crate::gameplay::fleet::ShipSpec;
+use crate::gameplay::session_rules::SessionRules;
 use crate::utils::dictionary::Dictionary;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -17,7 +19,16 @@ use std::convert::TryInto;
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct MatchmakingData {
     pub bump_seed: u8,
+    // Fleet rules for everyone joining the queue
+    pub fleet_budget: u32,
+    pub min_fleet_size: u8,
+    pub max_fleet_size: u8,
+
     pub queue: Vec<Pubkey>,
+    // Fleet each queued player picked
+    pub fleets: Dictionary<Pubkey, Vec<ShipSpec>>,
+    // Session rules each queued player asked for, players are only matched with someone who picked the same
+    pub rules: Dictionary<Pubkey, SessionRules>,
     pub active_games: Dictionary<Pubkey, u64>,
 }
 
@@ -82,7 +93,12 @@ pub fn get_or_create_data(
         Err(_) => {
             let new_matchmaking_data = MatchmakingData {
                 bump_seed: bump_seed,
+                fleet_budget: 100,
+                min_fleet_size: 1,
+                max_fleet_size: 5,
                 queue: Vec::new(),
+                fleets: Dictionary::new(),
+                rules: Dictionary::new(),
                 active_games: Dictionary::new(),
             };
             new_matchmaking_data
@@ -139,11 +155,23 @@ pub fn write_data(accounts: &[AccountInfo], matchmaking_data: &MatchmakingData)
 }
 
 impl MatchmakingData {
-    pub fn add_to_queue(&mut self, player: &Pubkey) {
+    pub fn add_to_queue(&mut self, player: &Pubkey, fleet: Vec<ShipSpec>, rules: SessionRules) {
         self.queue.push(*player);
+        self.fleets.insert(*player, fleet);
+        self.rules.insert(*player, rules);
     }
 
     pub fn leave_queue(&mut self, player: &Pubkey) {
         self.queue.retain(|&pubkey| pubkey != *player);
+        self.fleets.remove(player);
+        self.rules.remove(player);
+    }
+
+    // Longest waiting player that picked the same rules
+    pub fn find_opponent(&self, player: &Pubkey, rules: &SessionRules) -> Option<Pubkey> {
+        self.queue
+            .iter()
+            .find(|&queued| queued != player && self.rules.get(queued) == Some(rules))
+            .copied()
     }
 }
@@ -0,0 +1,190 @@
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::ship::SHIP_RADIUS;
+use crate::utils::vec2::Vector2;
+use solana_program::msg;
+
+// Number of steps each maneuver is split into when checking for collisions
+pub const MOVEMENT_STEPS: u32 = 20;
+
+// Degrees either side of the bow that count as ramming with the prow
+pub const PROW_ARC: f32 = 30.0;
+
+impl GameSession {
+    // Fly every maneuver at the same time in small steps. Ships that touch stop where they were
+    // just before the contact and ram each other. Returns the ids of every pair that collided
+    pub fn move_ships(&mut self) -> Vec<(u32, u32)> {
+        let starts = self.ships.clone();
+        let mut stopped: Vec<bool> = self.ships.iter().map(|s| s.health <= 0.0).collect();
+        let mut rams = Vec::new();
+
+        for step in 1..=MOVEMENT_STEPS {
+            let t = step as f32 / MOVEMENT_STEPS as f32;
+            let previous: Vec<(Vector2, f32)> = self
+                .ships
+                .iter()
+                .map(|s| (s.position(), s.rotation))
+                .collect();
+
+            for (i, ship) in self.ships.iter_mut().enumerate() {
+                if !stopped[i] {
+                    let (position, rotation) = starts[i].pose_along_maneuver(t);
+                    ship.set_pose(&position, rotation);
+                }
+            }
+
+            for a in 0..self.ships.len() {
+                for b in (a + 1)..self.ships.len() {
+                    // Two ships that already stopped can't run into each other anymore
+                    if self.ships[a].health <= 0.0
+                        || self.ships[b].health <= 0.0
+                        || (stopped[a] && stopped[b])
+                    {
+                        continue;
+                    }
+
+                    let delta = self.ships[a].position().subtract(&self.ships[b].position());
+                    let contact = SHIP_RADIUS * 2.0;
+                    if delta.sqr_magnitude() >= contact * contact {
+                        continue;
+                    }
+
+                    // A ship that had already stopped hits with no speed at all
+                    let mut speeds = [0.0; 2];
+                    for (speed, i) in speeds.iter_mut().zip([a, b]) {
+                        if !stopped[i] {
+                            *speed = starts[i].maneuver_data.speed;
+                            let (position, rotation) = previous[i];
+                            self.ships[i].set_pose(&position, rotation);
+                            stopped[i] = true;
+                        }
+                    }
+                    rams.push((a, b, speeds[0], speeds[1]));
+                }
+            }
+        }
+
+        for (a, b, a_speed, b_speed) in rams.iter() {
+            self.ram(*a, *b, *a_speed, *b_speed);
+        }
+
+        rams.iter()
+            .map(|(a, b, _, _)| (self.ships[*a].id, self.ships[*b].id))
+            .collect()
+    }
+
+    // Both ships take damage based on how fast the other one was going when they touched. Hitting with the prow
+    // halves the damage the rammer takes
+    fn ram(&mut self, a: usize, b: usize, a_speed: f32, b_speed: f32) {
+        let mut damage_to_a = self.rules.ram_damage + self.rules.ram_damage_per_speed * b_speed;
+        let mut damage_to_b = self.rules.ram_damage + self.rules.ram_damage_per_speed * a_speed;
+
+        if self.ships[a].is_facing(&self.ships[b].position(), PROW_ARC) {
+            damage_to_a *= 0.5;
+        }
+        if self.ships[b].is_facing(&self.ships[a].position(), PROW_ARC) {
+            damage_to_b *= 0.5;
+        }
+
+        msg!(
+            "Ship {} rammed ship {} for {} and took {} damage",
+            self.ships[a].id,
+            self.ships[b].id,
+            damage_to_b,
+            damage_to_a
+        );
+
+        self.ships[a].health -= damage_to_a;
+        self.ships[b].health -= damage_to_b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::ship_class::ShipClass;
+    use crate::gameplay::test_session::*;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn ships_that_pass_each_other_keep_flying() {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship],
+            SessionRules::default(),
+        );
+        place(&mut session, 1, 10.0, 0.0, -90.0);
+
+        let session = plan(&session, &[(0, straight(20.0)), (1, straight(20.0))]);
+        assert!(session.last_action_data.is_empty());
+        assert!((session.ships[0].y_position + 10.0).abs() < 0.01);
+        assert!((session.ships[1].y_position + 20.0).abs() < 0.01);
+        assert_eq!(session.ships[0].health, 100.0);
+    }
+
+    #[test]
+    fn head_on_collision_stops_both_ships_and_rams() {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship],
+            SessionRules::default(),
+        );
+        place(&mut session, 1, 0.0, -5.0, -90.0);
+
+        let session = plan(&session, &[(0, straight(20.0)), (1, straight(20.0))]);
+        assert_eq!(session.last_action_data, vec![0, 1]);
+
+        // Stopped just before touching, instead of flying through each other
+        let gap = session.ships[1].y_position - session.ships[0].y_position;
+        assert!((4.0..6.0).contains(&gap));
+
+        // Both hit with the prow, so both take half of 10 + 1.5 * 20
+        assert_eq!(session.ships[0].health, 80.0);
+        assert_eq!(session.ships[1].health, 80.0);
+    }
+
+    #[test]
+    fn ship_that_already_stopped_rams_with_no_speed() {
+        let mut session = new_game(
+            &[ShipClass::Longship, ShipClass::Longship],
+            &[ShipClass::Longship],
+            SessionRules::default(),
+        );
+        place(&mut session, 0, 0.0, -10.0, 90.0);
+        place(&mut session, 1, 0.0, -25.0, 90.0);
+        place(&mut session, 2, 0.0, 5.0, -90.0);
+
+        // Ship 0 and 2 meet head on, then ship 1 runs into the back of the stopped ship 0
+        let session = plan(
+            &session,
+            &[
+                (0, straight(10.0)),
+                (1, straight(20.0)),
+                (2, straight(10.0)),
+            ],
+        );
+        assert_eq!(session.last_action_data, vec![0, 2, 0, 1]);
+
+        // Ship 0 takes half of 10 + 1.5 * 10 from the head on ram and all of 10 + 1.5 * 20 in the back
+        assert_eq!(session.ships[0].health, 47.5);
+        // Ship 1 hits with the prow and ship 0 wasn't moving anymore, so half of 10
+        assert_eq!(session.ships[1].health, 95.0);
+        assert_eq!(session.ships[2].health, 87.5);
+    }
+
+    #[test]
+    fn fleets_that_ram_each_other_to_death_draw() {
+        let mut session = new_game(
+            &[ShipClass::Longship],
+            &[ShipClass::Longship],
+            SessionRules::default(),
+        );
+        place(&mut session, 1, 0.0, -5.0, -90.0);
+        session.ships[0].health = 5.0;
+        session.ships[1].health = 5.0;
+
+        let session = plan(&session, &[(0, straight(20.0)), (1, straight(20.0))]);
+        assert!(session.is_finished());
+        assert!(session.is_draw());
+        assert_eq!(session.winning_player, Pubkey::default());
+    }
+}
@@ -1,4 +1,5 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use crate::utils::vec2::Vector2;
 
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub struct Obstacle {
@@ -8,3 +9,62 @@ pub struct Obstacle {
     pub size: f32,
     pub health: u32,
 }
+
+impl Obstacle {
+    pub fn position(&self) -> Vector2 {
+        Vector2 {
+            x: self.x_position,
+            y: self.y_position,
+        }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.health == 0
+    }
+
+    // Whether a circle at this position overlaps the obstacle
+    pub fn overlaps(&self, position: &Vector2, radius: f32) -> bool {
+        if self.is_destroyed() {
+            return false;
+        }
+
+        let reach = self.size + radius;
+        position.subtract(&self.position()).sqr_magnitude() < reach * reach
+    }
+
+    // Whether the obstacle is in the way of a shot between two points
+    pub fn blocks_line(&self, from: &Vector2, to: &Vector2) -> bool {
+        if self.is_destroyed() {
+            return false;
+        }
+
+        self.position().distance_to_segment(from, to) < self.size
+    }
+
+    pub fn take_damage(&mut self, damage: f32) {
+        self.health = self.health.saturating_sub(damage.max(0.0) as u32);
+    }
+}
+
+// Obstacles every new game starts with, mirrored so neither side has an advantage
+pub fn default_layout() -> Vec<Obstacle> {
+    let layout = [
+        (0.0, 0.0, 6.0, 120),
+        (-26.0, -6.0, 4.0, 80),
+        (26.0, 6.0, 4.0, 80),
+        (-12.0, 16.0, 3.0, 40),
+        (12.0, -16.0, 3.0, 40),
+    ];
+
+    layout
+        .iter()
+        .enumerate()
+        .map(|(id, (x, y, size, health))| Obstacle {
+            id: id as u32,
+            x_position: *x,
+            y_position: *y,
+            size: *size,
+            health: *health,
+        })
+        .collect()
+}
@@ -0,0 +1,156 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use solana_program::msg;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum GamePhase {
+    // Session account exists but the players haven't been seated yet
+    Lobby,
+    // Players place their ships before the first turn
+    Deployment {
+        player1_ready: bool,
+        player2_ready: bool,
+    },
+    // Both players reveal the placements they committed to during deployment
+    DeploymentReveal {
+        player1_revealed: bool,
+        player2_revealed: bool,
+    },
+    // Both players pick a maneuver for each of their ships
+    Planning {
+        player1_ready: bool,
+        player2_ready: bool,
+    },
+    // Both players reveal the maneuvers they committed to during planning
+    Reveal {
+        player1_revealed: bool,
+        player2_revealed: bool,
+    },
+    // Players take turns activating their ships
+    Action,
+    // A winner has been decided, nothing can change anymore
+    Finished,
+}
+
+impl GamePhase {
+    pub fn deployment() -> Self {
+        GamePhase::Deployment {
+            player1_ready: false,
+            player2_ready: false,
+        }
+    }
+
+    pub fn deployment_reveal() -> Self {
+        GamePhase::DeploymentReveal {
+            player1_revealed: false,
+            player2_revealed: false,
+        }
+    }
+
+    pub fn planning() -> Self {
+        GamePhase::Planning {
+            player1_ready: false,
+            player2_ready: false,
+        }
+    }
+
+    pub fn reveal() -> Self {
+        GamePhase::Reveal {
+            player1_revealed: false,
+            player2_revealed: false,
+        }
+    }
+
+    // Per player flags of the phases that wait for both players, ready while committing and
+    // revealed while revealing
+    pub fn player_flags(&self) -> Option<(bool, bool)> {
+        let mut phase = *self;
+        phase
+            .player_flags_mut()
+            .map(|(player1, player2)| (*player1, *player2))
+    }
+
+    pub fn player_flags_mut(&mut self) -> Option<(&mut bool, &mut bool)> {
+        match self {
+            GamePhase::Deployment {
+                player1_ready,
+                player2_ready,
+            }
+            | GamePhase::Planning {
+                player1_ready,
+                player2_ready,
+            } => Some((player1_ready, player2_ready)),
+            GamePhase::DeploymentReveal {
+                player1_revealed,
+                player2_revealed,
+            }
+            | GamePhase::Reveal {
+                player1_revealed,
+                player2_revealed,
+            } => Some((player1_revealed, player2_revealed)),
+            GamePhase::Lobby | GamePhase::Action | GamePhase::Finished => None,
+        }
+    }
+
+    // Transition table, every phase change has to be listed here
+    pub fn can_transition_to(&self, next: &GamePhase) -> bool {
+        matches!(
+            (self, next),
+            (GamePhase::Lobby, GamePhase::Deployment { .. })
+                | (
+                    GamePhase::Deployment { .. },
+                    GamePhase::DeploymentReveal { .. }
+                )
+                | (
+                    GamePhase::DeploymentReveal { .. },
+                    GamePhase::Planning { .. }
+                )
+                | (GamePhase::Planning { .. }, GamePhase::Reveal { .. })
+                | (GamePhase::Reveal { .. }, GamePhase::Action)
+                | (GamePhase::Action, GamePhase::Planning { .. })
+                | (GamePhase::Deployment { .. }, GamePhase::Finished)
+                | (GamePhase::DeploymentReveal { .. }, GamePhase::Finished)
+                | (GamePhase::Planning { .. }, GamePhase::Finished)
+                | (GamePhase::Reveal { .. }, GamePhase::Finished)
+                | (GamePhase::Action, GamePhase::Finished)
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GamePhase::Lobby => "Lobby",
+            GamePhase::Deployment { .. } => "Deployment",
+            GamePhase::DeploymentReveal { .. } => "DeploymentReveal",
+            GamePhase::Planning { .. } => "Planning",
+            GamePhase::Reveal { .. } => "Reveal",
+            GamePhase::Action => "Action",
+            GamePhase::Finished => "Finished",
+        }
+    }
+}
+
+impl GameSession {
+    pub fn transition(&mut self, next: GamePhase) -> Result<(), GameError> {
+        if !self.phase.can_transition_to(&next) {
+            msg!(
+                "Can't go from the {} phase to the {} phase",
+                self.phase.name(),
+                next.name()
+            );
+            return Err(GameError::WrongPhase);
+        }
+
+        self.phase = next;
+        self.start_timer();
+        Ok(())
+    }
+
+    // Restart the deadline for whatever the current phase is waiting on
+    pub fn start_timer(&mut self) {
+        self.phase_deadline = match self.rules.timeout_for(&self.phase) {
+            Some(timeout) => self.updated_at.saturating_add(timeout),
+            None => i64::MAX,
+        };
+    }
+}
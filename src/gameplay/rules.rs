@@ -0,0 +1,1450 @@
+use crate::error::GameError;
+use crate::gameplay::critical::{DamagedSystems, ShipSystem};
+use crate::gameplay::damage::HitDamage;
+use crate::gameplay::deployment::Placement;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::line_of_sight::{LineOfSight, SightTarget};
+use crate::gameplay::phase::GamePhase;
+use crate::gameplay::seat::Seat;
+use crate::gameplay::session_rules::{InvalidManeuverRule, TimeoutPenalty};
+use crate::gameplay::ship::{ManeuverData, Ship, WeaponData, SHIP_RADIUS};
+use crate::gameplay::shot::ShotBreakdown;
+use crate::gameplay::upgrade;
+use crate::utils::dictionary::Dictionary;
+use crate::utils::random;
+use crate::utils::vec2::Vector2;
+use solana_program::{msg, pubkey::Pubkey};
+
+// Everything the rules need to know about the world outside of the session
+pub struct ActionContext {
+    pub player: Pubkey,
+    pub unix_timestamp: i64,
+    pub recent_slot_hash: [u8; 32],
+}
+
+pub enum GameAction {
+    CommitDeployment {
+        commitment: [u8; 32],
+    },
+    RevealDeployment {
+        placements: Dictionary<u32, Placement>,
+        salt: [u8; 32],
+    },
+    CommitManeuvers {
+        commitment: [u8; 32],
+    },
+    RevealManeuvers {
+        maneuver_data: Dictionary<u32, ManeuverData>,
+        salt: [u8; 32],
+    },
+    ClaimTimeout {},
+    FlagClock {},
+    FireWeapon {
+        ship_id: u32,
+        mount_index: u32,
+        target_id: u32,
+    },
+    FireAtObstacle {
+        ship_id: u32,
+        mount_index: u32,
+        obstacle_id: u32,
+    },
+    Upgrade {
+        ship_id: u32,
+        upgrade_id: u32,
+    },
+    Skip {
+        ship_id: u32,
+    },
+    Repair {
+        ship_id: u32,
+    },
+    Brace {
+        ship_id: u32,
+    },
+    Evade {
+        ship_id: u32,
+    },
+    Concede {},
+}
+
+impl GameAction {
+    // Which phases each action can be sent in
+    pub fn is_allowed_in(&self, phase: &GamePhase) -> bool {
+        match self {
+            GameAction::CommitDeployment { .. } => matches!(phase, GamePhase::Deployment { .. }),
+            GameAction::RevealDeployment { .. } => {
+                matches!(phase, GamePhase::DeploymentReveal { .. })
+            }
+            GameAction::CommitManeuvers { .. } => matches!(phase, GamePhase::Planning { .. }),
+            GameAction::RevealManeuvers { .. } => matches!(phase, GamePhase::Reveal { .. }),
+            GameAction::ClaimTimeout {} | GameAction::FlagClock {} => matches!(
+                phase,
+                GamePhase::Deployment { .. }
+                    | GamePhase::DeploymentReveal { .. }
+                    | GamePhase::Planning { .. }
+                    | GamePhase::Reveal { .. }
+                    | GamePhase::Action
+            ),
+            GameAction::FireWeapon { .. }
+            | GameAction::FireAtObstacle { .. }
+            | GameAction::Upgrade { .. }
+            | GameAction::Skip { .. }
+            | GameAction::Repair { .. }
+            | GameAction::Brace { .. }
+            | GameAction::Evade { .. } => *phase == GamePhase::Action,
+            GameAction::Concede {} => matches!(
+                phase,
+                GamePhase::Deployment { .. }
+                    | GamePhase::DeploymentReveal { .. }
+                    | GamePhase::Planning { .. }
+                    | GamePhase::Reveal { .. }
+                    | GamePhase::Action
+            ),
+        }
+    }
+
+    // Every ship the action touches on behalf of the player
+    pub fn ship_ids(&self) -> Vec<u32> {
+        match self {
+            GameAction::RevealDeployment { placements, .. } => placements.keys().cloned().collect(),
+            GameAction::RevealManeuvers { maneuver_data, .. } => {
+                maneuver_data.keys().cloned().collect()
+            }
+            GameAction::FireWeapon { ship_id, .. }
+            | GameAction::FireAtObstacle { ship_id, .. }
+            | GameAction::Upgrade { ship_id, .. }
+            | GameAction::Skip { ship_id }
+            | GameAction::Repair { ship_id }
+            | GameAction::Brace { ship_id }
+            | GameAction::Evade { ship_id } => vec![*ship_id],
+            GameAction::CommitDeployment { .. }
+            | GameAction::CommitManeuvers { .. }
+            | GameAction::ClaimTimeout {}
+            | GameAction::FlagClock {}
+            | GameAction::Concede {} => Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameAction::CommitDeployment { .. } => "CommitDeployment",
+            GameAction::RevealDeployment { .. } => "RevealDeployment",
+            GameAction::CommitManeuvers { .. } => "CommitManeuvers",
+            GameAction::RevealManeuvers { .. } => "RevealManeuvers",
+            GameAction::ClaimTimeout {} => "ClaimTimeout",
+            GameAction::FlagClock {} => "FlagClock",
+            GameAction::FireWeapon { .. } => "FireWeapon",
+            GameAction::FireAtObstacle { .. } => "FireAtObstacle",
+            GameAction::Upgrade { .. } => "Upgrade",
+            GameAction::Skip { .. } => "Skip",
+            GameAction::Repair { .. } => "Repair",
+            GameAction::Brace { .. } => "Brace",
+            GameAction::Evade { .. } => "Evade",
+            GameAction::Concede {} => "Concede",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionResult {
+    DeploymentCommitted {
+        all_committed: bool,
+    },
+    DeploymentRevealed {
+        deployed: bool,
+    },
+    ManeuversCommitted {
+        all_committed: bool,
+    },
+    ManeuversRevealed {
+        resolved: bool,
+    },
+    TimeoutClaimed {
+        penalized_player: Option<Pubkey>,
+        forfeited: bool,
+    },
+    ClockFlagged {
+        losing_player: Pubkey,
+    },
+    WeaponFired {
+        ship_id: u32,
+        mount_index: u32,
+        target_id: u32,
+        hit: bool,
+        damage: f32,
+        shield_damage: f32,
+        hull_damage: f32,
+        critical: Option<ShipSystem>,
+        shot: ShotBreakdown,
+        scrap: u32,
+        roll: random::RollRecord,
+    },
+    ObstacleHit {
+        ship_id: u32,
+        mount_index: u32,
+        obstacle_id: u32,
+        hit: bool,
+        damage: f32,
+        destroyed: bool,
+        shot: ShotBreakdown,
+        roll: random::RollRecord,
+    },
+    Upgraded {
+        ship_id: u32,
+        upgrade_id: u32,
+    },
+    Skipped {
+        ship_id: u32,
+    },
+    Repaired {
+        ship_id: u32,
+        restored: f32,
+    },
+    Braced {
+        ship_id: u32,
+    },
+    Evading {
+        ship_id: u32,
+    },
+    Conceded {
+        winning_player: Pubkey,
+    },
+}
+
+impl GameSession {
+    // Apply an action for a player and return the resulting state, leaving this one untouched
+    pub fn apply(
+        &self,
+        context: &ActionContext,
+        action: GameAction,
+    ) -> Result<(GameSession, ActionResult), GameError> {
+        if !action.is_allowed_in(&self.phase) {
+            msg!(
+                "{} is not allowed during the {} phase",
+                action.name(),
+                self.phase.name()
+            );
+            return Err(GameError::WrongPhase);
+        }
+
+        let mut session = self.clone();
+        session.charge_clocks(context.unix_timestamp);
+        session.updated_at = context.unix_timestamp;
+        session.last_rolls = Vec::new();
+        session.last_shot = None;
+
+        // Timeouts and flags can be claimed by anyone, so there is no seat to check
+        match action {
+            GameAction::ClaimTimeout {} => {
+                let result = session.claim_timeout(context)?;
+                return Ok((session, result));
+            }
+            GameAction::FlagClock {} => {
+                let result = session.flag_clock()?;
+                return Ok((session, result));
+            }
+            _ => {}
+        }
+
+        let seat = self.authorize(&context.player, &action.ship_ids())?;
+
+        // Conceding is the only thing left to do once your clock ran out
+        let earns_increment = !matches!(action, GameAction::Concede {});
+        if earns_increment && session.has_time_bank() && session.time_left(seat) <= 0 {
+            msg!("{} has run out of time", context.player);
+            return Err(GameError::OutOfTime);
+        }
+
+        let result = match action {
+            GameAction::CommitDeployment { commitment } => ActionResult::DeploymentCommitted {
+                all_committed: session.commit_deployment(seat, commitment)?,
+            },
+            GameAction::RevealDeployment { placements, salt } => ActionResult::DeploymentRevealed {
+                deployed: session.reveal_deployment(seat, &placements, &salt)?,
+            },
+            GameAction::CommitManeuvers { commitment } => {
+                session.commit_maneuvers(seat, commitment)?
+            }
+            GameAction::RevealManeuvers {
+                maneuver_data,
+                salt,
+            } => session.reveal_maneuvers(seat, &maneuver_data, &salt)?,
+            // Handled above, before the seat check
+            GameAction::ClaimTimeout {} | GameAction::FlagClock {} => unreachable!(),
+            GameAction::FireWeapon {
+                ship_id,
+                mount_index,
+                target_id,
+            } => session.fire_weapon(seat, context, ship_id, mount_index, target_id)?,
+            GameAction::FireAtObstacle {
+                ship_id,
+                mount_index,
+                obstacle_id,
+            } => session.fire_at_obstacle(seat, context, ship_id, mount_index, obstacle_id)?,
+            GameAction::Upgrade {
+                ship_id,
+                upgrade_id,
+            } => session.upgrade(seat, ship_id, upgrade_id)?,
+            GameAction::Skip { ship_id } => session.skip(seat, ship_id)?,
+            GameAction::Repair { ship_id } => session.repair(seat, ship_id)?,
+            GameAction::Brace { ship_id } => session.brace(seat, ship_id)?,
+            GameAction::Evade { ship_id } => session.evade(seat, ship_id)?,
+            GameAction::Concede {} => session.concede(seat)?,
+        };
+
+        if earns_increment {
+            session.add_increment(seat);
+        }
+
+        Ok((session, result))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == GamePhase::Finished
+    }
+
+    // A finished game without a winner
+    pub fn is_draw(&self) -> bool {
+        self.is_finished() && self.winning_player == Pubkey::default()
+    }
+
+    fn commit_maneuvers(
+        &mut self,
+        seat: Seat,
+        commitment: [u8; 32],
+    ) -> Result<ActionResult, GameError> {
+        if !self.record_commitment(seat, commitment)? {
+            return Ok(ActionResult::ManeuversCommitted {
+                all_committed: false,
+            });
+        }
+
+        self.transition(GamePhase::reveal())?;
+
+        Ok(ActionResult::ManeuversCommitted {
+            all_committed: true,
+        })
+    }
+
+    fn reveal_maneuvers(
+        &mut self,
+        seat: Seat,
+        maneuver_data: &Dictionary<u32, ManeuverData>,
+        salt: &[u8; 32],
+    ) -> Result<ActionResult, GameError> {
+        // Maneuvers are hashed as a borsh Vec<(u32, ManeuverData)> ordered by ship id
+        let all_revealed = self.record_reveal(seat, &maneuver_data.as_sorted_vec(), salt)?;
+
+        // Check every maneuver against the ship's current limits before storing it
+        let invalid_maneuver_rule = self.rules.invalid_maneuver_rule;
+        let player = self.player(seat);
+        for ship in self
+            .ships
+            .iter_mut()
+            .filter(|s| s.owner == player && s.health > 0.0)
+        {
+            let maneuver = match maneuver_data.get(&ship.id) {
+                Some(maneuver) if ship.is_valid_maneuver(maneuver) => *maneuver,
+                Some(_) if invalid_maneuver_rule == InvalidManeuverRule::UseDefault => {
+                    msg!("Invalid maneuver for ship {}, using default", ship.id);
+                    ship.default_maneuver()
+                }
+                None if invalid_maneuver_rule == InvalidManeuverRule::UseDefault => {
+                    msg!("Missing maneuver for ship {}, using default", ship.id);
+                    ship.default_maneuver()
+                }
+                Some(_) => {
+                    msg!("Invalid maneuver for ship {}", ship.id);
+                    return Err(GameError::InvalidManeuver);
+                }
+                None => {
+                    msg!("Missing maneuver for ship {}", ship.id);
+                    return Err(GameError::MissingManeuver);
+                }
+            };
+
+            ship.maneuver_data = maneuver;
+        }
+
+        if all_revealed {
+            self.resolve_movement()?;
+        }
+
+        Ok(ActionResult::ManeuversRevealed {
+            resolved: all_revealed,
+        })
+    }
+
+    // Anyone can call this once the deadline passed, the player holding up the game gets penalized
+    fn claim_timeout(&mut self, context: &ActionContext) -> Result<ActionResult, GameError> {
+        if context.unix_timestamp <= self.phase_deadline {
+            msg!("Deadline has not passed yet");
+            return Err(GameError::DeadlineNotReached);
+        }
+
+        let stalling = self.stalling_seats();
+        let penalized_player = match stalling.as_slice() {
+            [seat] => {
+                // A player that committed but won't reveal always forfeits, otherwise they could hold back a bad plan
+                let always_forfeit = matches!(
+                    self.phase,
+                    GamePhase::DeploymentReveal { .. } | GamePhase::Reveal { .. }
+                );
+                if self.penalize(*seat, always_forfeit)? {
+                    return Ok(ActionResult::TimeoutClaimed {
+                        penalized_player: Some(self.player(*seat)),
+                        forfeited: true,
+                    });
+                }
+
+                Some(self.player(*seat))
+            }
+            // Both players stalled, so nobody was kept waiting and the game just moves on
+            _ => None,
+        };
+
+        self.auto_resolve(&stalling)?;
+
+        Ok(ActionResult::TimeoutClaimed {
+            penalized_player,
+            forfeited: false,
+        })
+    }
+
+    // Anyone can end the game for a player whose clock ran out
+    fn flag_clock(&mut self) -> Result<ActionResult, GameError> {
+        let seat = match self.flagged_seat() {
+            Some(seat) => seat,
+            None => {
+                msg!("Nobody has run out of time");
+                return Err(GameError::DeadlineNotReached);
+            }
+        };
+
+        self.winning_player = self.player(seat.opponent());
+        self.transition(GamePhase::Finished)?;
+
+        Ok(ActionResult::ClockFlagged {
+            losing_player: self.player(seat),
+        })
+    }
+
+    // Players the current phase is waiting on
+    pub fn stalling_seats(&self) -> Vec<Seat> {
+        let (player1_stalling, player2_stalling) = match self.phase {
+            GamePhase::Deployment {
+                player1_ready,
+                player2_ready,
+            } => (!player1_ready, !player2_ready),
+            GamePhase::DeploymentReveal {
+                player1_revealed,
+                player2_revealed,
+            } => (!player1_revealed, !player2_revealed),
+            GamePhase::Planning {
+                player1_ready,
+                player2_ready,
+            } => (!player1_ready, !player2_ready),
+            GamePhase::Reveal {
+                player1_revealed,
+                player2_revealed,
+            } => (!player1_revealed, !player2_revealed),
+            GamePhase::Action => (
+                self.active_player == self.player1,
+                self.active_player == self.player2,
+            ),
+            _ => (false, false),
+        };
+
+        let mut seats = Vec::new();
+        if player1_stalling {
+            seats.push(Seat::Player1);
+        }
+        if player2_stalling {
+            seats.push(Seat::Player2);
+        }
+        seats
+    }
+
+    // Give the player a strike, returns whether that cost them the game
+    fn penalize(&mut self, seat: Seat, always_forfeit: bool) -> Result<bool, GameError> {
+        let strikes = match seat {
+            Seat::Player1 => &mut self.player1_strikes,
+            Seat::Player2 => &mut self.player2_strikes,
+        };
+        *strikes = strikes.saturating_add(1);
+        let strikes = *strikes;
+
+        msg!("{} timed out, strike {}", self.player(seat), strikes);
+
+        let out_of_strikes = self.rules.max_strikes > 0 && strikes >= self.rules.max_strikes;
+        if always_forfeit || out_of_strikes || self.rules.timeout_penalty == TimeoutPenalty::Forfeit
+        {
+            self.winning_player = self.player(seat.opponent());
+            self.transition(GamePhase::Finished)?;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    // Do whatever the stalling players should have done
+    fn auto_resolve(&mut self, stalling: &[Seat]) -> Result<(), GameError> {
+        match self.phase {
+            GamePhase::Deployment { .. } => {
+                // Stalling players keep their starting line, which counts as revealed
+                self.transition(GamePhase::DeploymentReveal {
+                    player1_revealed: stalling.contains(&Seat::Player1),
+                    player2_revealed: stalling.contains(&Seat::Player2),
+                })?;
+
+                if stalling.len() == 2 {
+                    self.finish_deployment()?;
+                }
+            }
+            GamePhase::DeploymentReveal { .. } => self.finish_deployment()?,
+            GamePhase::Planning { .. } => {
+                // Stalling players fly their default maneuvers, which counts as revealed
+                for seat in stalling {
+                    self.fly_default_maneuvers(*seat);
+                }
+
+                self.transition(GamePhase::Reveal {
+                    player1_revealed: stalling.contains(&Seat::Player1),
+                    player2_revealed: stalling.contains(&Seat::Player2),
+                })?;
+
+                if stalling.len() == 2 {
+                    self.resolve_movement()?;
+                }
+            }
+            GamePhase::Reveal { .. } => {
+                for seat in stalling {
+                    self.fly_default_maneuvers(*seat);
+                }
+
+                self.resolve_movement()?;
+            }
+            GamePhase::Action => {
+                // Skip the first ship the active player hasn't activated yet
+                let ship_id = self
+                    .ships
+                    .iter()
+                    .find(|s| s.owner == self.active_player && s.health > 0.0 && !s.activated)
+                    .map(|s| s.id)
+                    .ok_or(GameError::InvalidShip)?;
+
+                self.last_action = 0;
+                self.last_action_data = vec![ship_id];
+                self.end_activation(ship_id)?;
+            }
+            _ => return Err(GameError::WrongPhase),
+        }
+
+        Ok(())
+    }
+
+    fn fly_default_maneuvers(&mut self, seat: Seat) {
+        let player = self.player(seat);
+        for ship in self
+            .ships
+            .iter_mut()
+            .filter(|s| s.owner == player && s.health > 0.0)
+        {
+            ship.maneuver_data = ship.default_maneuver();
+        }
+    }
+
+    // Move all ships once both plans are known
+    fn resolve_movement(&mut self) -> Result<(), GameError> {
+        let rams = self.move_ships();
+        self.enforce_arena();
+
+        for ship in self.ships.iter_mut() {
+            if ship.health <= 0.0 {
+                continue;
+            }
+
+            ship.aim_turrets();
+
+            // Ending a maneuver inside an obstacle damages the ship
+            let ship_pos = ship.position();
+            if let Some(obstacle) = self
+                .obstacles
+                .iter()
+                .find(|o| o.overlaps(&ship_pos, SHIP_RADIUS))
+            {
+                msg!("Ship {} collided with obstacle {}", ship.id, obstacle.id);
+                ship.health -= self.rules.obstacle_collision_damage;
+            }
+        }
+
+        self.player1_commitment = [0; 32];
+        self.player2_commitment = [0; 32];
+        self.last_action = 4;
+        self.last_action_data = rams.iter().flat_map(|(a, b)| [*a, *b]).collect();
+        if !self.check_victory()? {
+            self.transition(GamePhase::Action)?;
+        }
+
+        Ok(())
+    }
+
+    fn fire_weapon(
+        &mut self,
+        seat: Seat,
+        context: &ActionContext,
+        ship_id: u32,
+        mount_index: u32,
+        target_id: u32,
+    ) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        // Wrecks can't be shot again, that would let players farm scrap from them
+        match self.ships.get(target_id as usize) {
+            Some(target) if target_id != ship_id && target.health > 0.0 => {}
+            _ => {
+                msg!("Invalid target id");
+                return Err(GameError::InvalidTarget);
+            }
+        }
+
+        let ship = self.ships[ship_id as usize].clone();
+        let weapon = ship.weapon(mount_index)?;
+        let target = self.ships[target_id as usize].clone();
+        let sight = self.check_firing_solution(
+            &ship,
+            &weapon,
+            SightTarget::Ship(target_id),
+            &target.position(),
+            SHIP_RADIUS,
+        )?;
+
+        let shot = self.shot_breakdown(
+            &ship,
+            mount_index,
+            &weapon,
+            &target.position(),
+            &sight,
+            Some(&target),
+        );
+        let subject = [ship_id, target_id, mount_index];
+        let roll = self.roll(context, subject);
+
+        // Check if we hit or miss
+        let hit = roll.value < shot.hit_chance as f64;
+        let damage = if hit { shot.damage } else { 0.0 };
+
+        let mut destroyed = false;
+        let mut hit_damage = HitDamage {
+            shield_damage: 0.0,
+            hull_damage: 0.0,
+        };
+        if let Some(target) = self.ships.get_mut(target_id as usize) {
+            let was_alive = target.health > 0.0;
+            if hit {
+                hit_damage = target.take_hit(damage, weapon.damage_type);
+            }
+            destroyed = was_alive && target.health <= 0.0;
+
+            // Destroy ships
+            if target.health < 0.0 {
+                target.activated = true;
+                target.maneuver_data.speed = 0.0;
+                target.maneuver_data.angle = 0.0;
+            }
+        }
+
+        // Hits that get through to the hull can knock out one of the target's systems
+        let mut critical = None;
+        if hit_damage.hull_damage > 0.0 && self.ships[target_id as usize].health > 0.0 {
+            let critical_roll = self.roll(context, subject);
+            if critical_roll.value < self.rules.critical_chance as f64 {
+                let system = ShipSystem::from_roll(self.roll(context, subject).value);
+                msg!("Critical hit on the {:?} of ship {}", system, target_id);
+                self.ships[target_id as usize].damage_system(system);
+                critical = Some(system);
+            }
+        }
+
+        // Salvage scrap from enemy ships we hit, and more if we sink them
+        let mut scrap = 0;
+        if hit && target.owner != ship.owner {
+            scrap += self.rules.scrap_per_hit;
+            if destroyed {
+                scrap += self.rules.scrap_per_kill;
+            }
+            self.earn_scrap(seat, scrap);
+        }
+
+        if hit {
+            msg!(
+                "Ship {} took {} shield damage and {} hull damage",
+                target_id,
+                hit_damage.shield_damage,
+                hit_damage.hull_damage
+            );
+        }
+
+        self.last_action = 1;
+        self.last_action_data = vec![
+            ship_id,
+            target_id,
+            mount_index,
+            hit_damage.shield_damage.round() as u32,
+            hit_damage.hull_damage.round() as u32,
+        ];
+        self.last_shot = Some(shot.clone());
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::WeaponFired {
+            ship_id,
+            mount_index,
+            target_id,
+            hit,
+            damage,
+            shield_damage: hit_damage.shield_damage,
+            hull_damage: hit_damage.hull_damage,
+            critical,
+            shot,
+            scrap,
+            roll,
+        })
+    }
+
+    fn fire_at_obstacle(
+        &mut self,
+        seat: Seat,
+        context: &ActionContext,
+        ship_id: u32,
+        mount_index: u32,
+        obstacle_id: u32,
+    ) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        let (obstacle_pos, obstacle_size) = match self.obstacles.get(obstacle_id as usize) {
+            Some(obstacle) if !obstacle.is_destroyed() => (obstacle.position(), obstacle.size),
+            _ => {
+                msg!("Invalid obstacle id");
+                return Err(GameError::InvalidTarget);
+            }
+        };
+
+        let ship = self.ships[ship_id as usize].clone();
+        let weapon = ship.weapon(mount_index)?;
+        let sight = self.check_firing_solution(
+            &ship,
+            &weapon,
+            SightTarget::Obstacle(obstacle_id),
+            &obstacle_pos,
+            obstacle_size,
+        )?;
+
+        let shot = self.shot_breakdown(&ship, mount_index, &weapon, &obstacle_pos, &sight, None);
+        let roll = self.roll(context, [ship_id, obstacle_id, mount_index]);
+
+        // Check if we hit or miss
+        let hit = roll.value < shot.hit_chance as f64;
+        let damage = if hit { shot.damage } else { 0.0 };
+
+        let obstacle = &mut self.obstacles[obstacle_id as usize];
+        obstacle.take_damage(damage);
+        let destroyed = obstacle.is_destroyed();
+
+        self.last_action = 3;
+        self.last_action_data = vec![ship_id, obstacle_id, mount_index];
+        self.last_shot = Some(shot.clone());
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::ObstacleHit {
+            ship_id,
+            mount_index,
+            obstacle_id,
+            hit,
+            damage,
+            destroyed,
+            shot,
+            roll,
+        })
+    }
+
+    // Arc, range and line of sight checks for anything a ship shoots at
+    fn check_firing_solution(
+        &self,
+        ship: &Ship,
+        weapon: &WeaponData,
+        target: SightTarget,
+        target_pos: &Vector2,
+        target_radius: f32,
+    ) -> Result<LineOfSight, GameError> {
+        let ship_pos = ship.position();
+        let delta = target_pos.subtract(&ship_pos);
+
+        // Outside of firing arc, which is centred on the mount's facing
+        let angle = ship
+            .weapon_direction(weapon)
+            .angle_between(&delta)
+            .to_degrees();
+        if angle.abs() > ship.effective_arc(weapon) {
+            msg!("Target outside firing arc");
+            return Err(GameError::InvalidTarget);
+        }
+
+        // Outside of range
+        let distance_squared = delta.sqr_magnitude();
+        let range_squared = weapon.range * weapon.range;
+        if distance_squared > range_squared {
+            msg!("Target outside range");
+            return Err(GameError::InvalidTarget);
+        }
+
+        // Obstacles and other ships can hide the target partially or completely
+        let sight = self.line_of_sight(ship.id, target, target_pos, target_radius);
+        if sight.is_blocked() {
+            msg!("Target is completely hidden");
+            return Err(GameError::NoLineOfSight);
+        }
+
+        Ok(sight)
+    }
+
+    fn upgrade(
+        &mut self,
+        seat: Seat,
+        ship_id: u32,
+        upgrade_id: u32,
+    ) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        if self.ships[ship_id as usize].damaged_systems.crew {
+            msg!("Crew of ship {} is injured", ship_id);
+            return Err(GameError::CrewInjured);
+        }
+
+        // Upgrades are paid for with scrap
+        let scrap_cost = match upgrade::find_upgrade(upgrade_id) {
+            Some(upgrade) => upgrade.scrap_cost,
+            None => {
+                msg!("Invalid upgrade_id: {}", upgrade_id);
+                return Err(GameError::UnknownUpgrade);
+            }
+        };
+        self.spend_scrap(seat, scrap_cost)?;
+        self.ships[ship_id as usize].apply_upgrade(upgrade_id)?;
+
+        self.last_action = 2;
+        self.last_action_data = vec![ship_id, upgrade_id];
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::Upgraded {
+            ship_id,
+            upgrade_id,
+        })
+    }
+
+    fn skip(&mut self, seat: Seat, ship_id: u32) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        self.last_action = 0;
+        self.last_action_data = vec![ship_id];
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::Skipped { ship_id })
+    }
+
+    fn repair(&mut self, seat: Seat, ship_id: u32) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        // Can't patch a ship up past its original hull, but every damaged system gets fixed
+        let repair_amount = self.rules.repair_amount;
+        let ship = &mut self.ships[ship_id as usize];
+        let restored = repair_amount.min(ship.max_health - ship.health).max(0.0);
+        ship.health += restored;
+        ship.damaged_systems = DamagedSystems::none();
+        msg!("Ship {} repaired {} health", ship_id, restored);
+
+        self.last_action = 5;
+        self.last_action_data = vec![ship_id, restored as u32];
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::Repaired { ship_id, restored })
+    }
+
+    fn brace(&mut self, seat: Seat, ship_id: u32) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        self.ships[ship_id as usize].braced = true;
+
+        self.last_action = 6;
+        self.last_action_data = vec![ship_id];
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::Braced { ship_id })
+    }
+
+    fn evade(&mut self, seat: Seat, ship_id: u32) -> Result<ActionResult, GameError> {
+        self.check_activation(seat, ship_id)?;
+
+        self.ships[ship_id as usize].evading = true;
+
+        self.last_action = 7;
+        self.last_action_data = vec![ship_id];
+        self.end_activation(ship_id)?;
+
+        Ok(ActionResult::Evading { ship_id })
+    }
+
+    fn concede(&mut self, seat: Seat) -> Result<ActionResult, GameError> {
+        self.winning_player = self.player(seat.opponent());
+        self.transition(GamePhase::Finished)?;
+
+        Ok(ActionResult::Conceded {
+            winning_player: self.winning_player,
+        })
+    }
+
+    // Shared checks for every action that activates a ship, ownership is already checked by authorize
+    fn check_activation(&self, seat: Seat, ship_id: u32) -> Result<(), GameError> {
+        if self.player(seat) != self.active_player {
+            msg!("Not the active player");
+            return Err(GameError::InvalidShip);
+        }
+
+        let ship = &self.ships[ship_id as usize];
+        if ship.activated {
+            msg!("Ship already activated");
+            return Err(GameError::InvalidShip);
+        }
+
+        if ship.health <= 0.0 {
+            msg!("Ship is destroyed");
+            return Err(GameError::InvalidShip);
+        }
+
+        Ok(())
+    }
+
+    // Every roll uses the next nonce, so rolls are independent even within the same slot
+    fn roll(&mut self, context: &ActionContext, subject: [u32; 3]) -> random::RollRecord {
+        let roll = random::roll(
+            &self.entropy,
+            &context.recent_slot_hash,
+            self.roll_nonce,
+            &subject,
+        );
+        self.roll_nonce += 1;
+        self.last_rolls.push(roll);
+
+        msg!(
+            "Roll {} with slot hash {} gave {}",
+            roll.nonce,
+            Pubkey::new_from_array(roll.slot_hash),
+            roll.value
+        );
+
+        roll
+    }
+
+    // Declare a winner once one of the players has no ships left, or a draw if neither has
+    fn check_victory(&mut self) -> Result<bool, GameError> {
+        let player1_has_alive_ship = self
+            .ships
+            .iter()
+            .any(|x| x.owner == self.player1 && x.health > 0.0);
+        let player2_has_alive_ship = self
+            .ships
+            .iter()
+            .any(|x| x.owner == self.player2 && x.health > 0.0);
+
+        match (player1_has_alive_ship, player2_has_alive_ship) {
+            (true, true) => return Ok(false),
+            // Both fleets went down together, nobody wins
+            (false, false) => self.winning_player = Pubkey::default(),
+            // Player 2 wins!
+            (false, true) => self.winning_player = self.player2,
+            // Player 1 wins!
+            (true, false) => self.winning_player = self.player1,
+        }
+
+        self.transition(GamePhase::Finished)?;
+        Ok(true)
+    }
+
+    // Mark the ship as activated and hand the turn to whoever is up next
+    fn end_activation(&mut self, ship_id: u32) -> Result<(), GameError> {
+        if let Some(ship) = self.ships.get_mut(ship_id as usize) {
+            ship.activated = true;
+        }
+
+        if self.check_victory()? {
+            return Ok(());
+        }
+
+        // This means all ships have activated
+        if self.ships.iter().all(|s| s.health <= 0.0 || s.activated) {
+            // The storm hits at the end of every turn
+            self.apply_storm();
+            if self.check_victory()? {
+                return Ok(());
+            }
+
+            let scrap_per_turn = self.rules.scrap_per_turn;
+            self.earn_scrap(Seat::Player1, scrap_per_turn);
+            self.earn_scrap(Seat::Player2, scrap_per_turn);
+
+            self.turn += 1;
+            self.active_player = self.player1;
+            self.transition(GamePhase::planning())?;
+
+            // Reset ship activation and recharge shields, but only if ship isn't destroyed
+            for s in &mut self.ships {
+                s.braced = false;
+                s.evading = false;
+                s.damaged_systems.crew = false;
+
+                if s.health <= 0.0 {
+                    continue;
+                }
+
+                s.activated = false;
+                s.regenerate_shields();
+            }
+        }
+        // Toggle active player, but only if the other player has at least one non-activated ship
+        else {
+            if self
+                .ships
+                .iter()
+                .any(|s| s.owner != self.active_player && s.health > 0.0 && !s.activated)
+            {
+                if self.active_player == self.player1 {
+                    self.active_player = self.player2;
+                } else {
+                    self.active_player = self.player1;
+                }
+            }
+
+            // Every activation gets its own deadline
+            self.start_timer();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::test_session::*;
+
+    fn commit(session: &GameSession, player: &Pubkey, seat: Seat, speed: f32) -> GameSession {
+        let ship_id = seat as u32;
+        let planned = maneuvers_for(session, player, &[(ship_id, straight(speed))]);
+        let commitment = commitment_for(&planned, seat);
+        apply(session, player, GameAction::CommitManeuvers { commitment }).unwrap()
+    }
+
+    fn reveal(
+        session: &GameSession,
+        player: &Pubkey,
+        seat: Seat,
+        speed: f32,
+    ) -> Result<GameSession, GameError> {
+        let ship_id = seat as u32;
+        let maneuver_data = maneuvers_for(session, player, &[(ship_id, straight(speed))]);
+        apply(
+            session,
+            player,
+            GameAction::RevealManeuvers {
+                maneuver_data,
+                salt: salt(seat),
+            },
+        )
+    }
+
+    #[test]
+    fn apply_leaves_the_session_untouched() {
+        let session = duel_in_action();
+        let before = session.try_to_vec().unwrap();
+
+        let fire = || GameAction::FireWeapon {
+            ship_id: 0,
+            mount_index: 0,
+            target_id: 1,
+        };
+        let (next, result) = session.apply(&context(&PLAYER1, 0), fire()).unwrap();
+        assert_eq!(session.try_to_vec().unwrap(), before);
+        assert_ne!(next.try_to_vec().unwrap(), before);
+
+        // Same state and context give the same result
+        let (_, again) = session.apply(&context(&PLAYER1, 0), fire()).unwrap();
+        assert_eq!(result, again);
+    }
+
+    #[test]
+    fn actions_outside_their_phase_are_rejected() {
+        let planning = duel(SessionRules::default());
+        let fire = GameAction::FireWeapon {
+            ship_id: 0,
+            mount_index: 0,
+            target_id: 1,
+        };
+        assert!(matches!(
+            apply(&planning, &PLAYER1, fire),
+            Err(GameError::WrongPhase)
+        ));
+
+        let action = duel_in_action();
+        let commit = GameAction::CommitManeuvers {
+            commitment: [0; 32],
+        };
+        assert!(matches!(
+            apply(&action, &PLAYER1, commit),
+            Err(GameError::WrongPhase)
+        ));
+    }
+
+    #[test]
+    fn only_players_can_act_and_only_with_their_own_ships() {
+        let session = duel_in_action();
+        assert!(matches!(
+            apply(&session, &OUTSIDER, GameAction::Skip { ship_id: 0 }),
+            Err(GameError::NotAPlayer)
+        ));
+        assert!(matches!(
+            apply(&session, &PLAYER1, GameAction::Skip { ship_id: 1 }),
+            Err(GameError::NotShipOwner)
+        ));
+        assert!(matches!(
+            apply(&session, &PLAYER1, GameAction::Skip { ship_id: 7 }),
+            Err(GameError::InvalidShip)
+        ));
+    }
+
+    #[test]
+    fn conceding_hands_the_win_to_the_opponent() {
+        let session = duel(SessionRules::default());
+        let session = apply(&session, &PLAYER1, GameAction::Concede {}).unwrap();
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER2);
+    }
+
+    #[test]
+    fn revealed_maneuvers_are_flown_once_both_players_revealed() {
+        let session = duel(SessionRules::default());
+        let session = commit(&session, &PLAYER1, Seat::Player1, 20.0);
+        let session = commit(&session, &PLAYER2, Seat::Player2, 15.0);
+        assert_eq!(session.phase, GamePhase::reveal());
+
+        let session = reveal(&session, &PLAYER1, Seat::Player1, 20.0).unwrap();
+        assert_eq!(session.ships[0].y_position, -30.0);
+
+        let session = reveal(&session, &PLAYER2, Seat::Player2, 15.0).unwrap();
+        assert_eq!(session.phase, GamePhase::Action);
+        assert!((session.ships[0].y_position + 10.0).abs() < 0.01);
+        assert!((session.ships[1].y_position - 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn reveal_has_to_match_the_commitment() {
+        let session = duel(SessionRules::default());
+        let session = commit(&session, &PLAYER1, Seat::Player1, 20.0);
+        let session = commit(&session, &PLAYER2, Seat::Player2, 20.0);
+
+        let result = reveal(&session, &PLAYER1, Seat::Player1, 15.0);
+        assert!(matches!(result, Err(GameError::InvalidReveal)));
+    }
+
+    #[test]
+    fn players_commit_and_reveal_only_once() {
+        let session = duel(SessionRules::default());
+        let session = commit(&session, &PLAYER1, Seat::Player1, 20.0);
+        let result = apply(
+            &session,
+            &PLAYER1,
+            GameAction::CommitManeuvers {
+                commitment: [5; 32],
+            },
+        );
+        assert!(matches!(result, Err(GameError::AlreadyCommitted)));
+
+        let session = commit(&session, &PLAYER2, Seat::Player2, 20.0);
+        let session = reveal(&session, &PLAYER1, Seat::Player1, 20.0).unwrap();
+        let result = reveal(&session, &PLAYER1, Seat::Player1, 20.0);
+        assert!(matches!(result, Err(GameError::AlreadyRevealed)));
+    }
+
+    #[test]
+    fn player_that_never_reveals_forfeits() {
+        let session = duel(SessionRules::default());
+        let session = commit(&session, &PLAYER1, Seat::Player1, 20.0);
+        let session = commit(&session, &PLAYER2, Seat::Player2, 20.0);
+        let session = reveal(&session, &PLAYER1, Seat::Player1, 20.0).unwrap();
+        let deadline = session.phase_deadline;
+
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER1);
+    }
+
+    #[test]
+    fn rolls_depend_on_the_shot() {
+        let shot = random::roll(&[1; 32], &[2; 32], 0, &[0, 1, 0]);
+        let other_target = random::roll(&[1; 32], &[2; 32], 0, &[0, 2, 0]);
+        let other_mount = random::roll(&[1; 32], &[2; 32], 0, &[0, 1, 1]);
+
+        assert_ne!(shot.value, other_target.value);
+        assert_ne!(shot.value, other_mount.value);
+    }
+
+    #[test]
+    fn invalid_maneuvers_are_rejected_or_replaced_by_the_rules() {
+        let session = duel(SessionRules {
+            invalid_maneuver_rule: InvalidManeuverRule::Reject,
+            ..SessionRules::default()
+        });
+        let session = commit(&session, &PLAYER1, Seat::Player1, 50.0);
+        let session = commit(&session, &PLAYER2, Seat::Player2, 20.0);
+        let result = reveal(&session, &PLAYER1, Seat::Player1, 50.0);
+        assert!(matches!(result, Err(GameError::InvalidManeuver)));
+
+        // Longships fly their minimum speed of 10 instead
+        let session = plan(
+            &duel(SessionRules::default()),
+            &[(0, straight(50.0)), (1, straight(20.0))],
+        );
+        assert!((session.ships[0].y_position + 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn timeouts_can_only_be_claimed_after_the_deadline() {
+        let session = duel(SessionRules::default());
+        let deadline = session.phase_deadline;
+
+        let result = apply_at(&session, &OUTSIDER, GameAction::ClaimTimeout {}, deadline);
+        assert!(matches!(result, Err(GameError::DeadlineNotReached)));
+
+        // Nobody committed, so both fleets fly their default maneuvers without a strike
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert_eq!(session.phase, GamePhase::Action);
+        assert_eq!(session.player1_strikes, 0);
+        assert_eq!(session.player2_strikes, 0);
+        assert!((session.ships[0].y_position + 20.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn stalling_player_gets_a_strike_and_flies_default_maneuvers() {
+        let session = duel(SessionRules::default());
+        let session = commit(&session, &PLAYER1, Seat::Player1, 20.0);
+        let deadline = session.phase_deadline;
+
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert_eq!(session.player2_strikes, 1);
+        assert_eq!(
+            session.phase,
+            GamePhase::Reveal {
+                player1_revealed: false,
+                player2_revealed: true,
+            }
+        );
+    }
+
+    #[test]
+    fn forfeit_penalty_ends_the_game_on_the_first_timeout() {
+        let session = duel(SessionRules {
+            timeout_penalty: TimeoutPenalty::Forfeit,
+            ..SessionRules::default()
+        });
+        let session = commit(&session, &PLAYER2, Seat::Player2, 20.0);
+        let deadline = session.phase_deadline;
+
+        let session = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::ClaimTimeout {},
+            deadline + 1,
+        )
+        .unwrap();
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER2);
+    }
+
+    #[test]
+    fn upgrades_cost_scrap() {
+        let session = duel_in_action();
+        let upgraded = apply(
+            &session,
+            &PLAYER1,
+            GameAction::Upgrade {
+                ship_id: 0,
+                upgrade_id: 1,
+            },
+        )
+        .unwrap();
+        assert_eq!(upgraded.player1_scrap, 0);
+        assert_eq!(upgraded.ships[0].max_speed, 30.0);
+
+        let mut session = session;
+        session.player1_scrap = 4;
+        let result = apply(
+            &session,
+            &PLAYER1,
+            GameAction::Upgrade {
+                ship_id: 0,
+                upgrade_id: 1,
+            },
+        );
+        assert!(matches!(result, Err(GameError::NotEnoughScrap)));
+    }
+
+    #[test]
+    fn every_turn_pays_scrap() {
+        let session = duel_in_action();
+        let session = apply(&session, &PLAYER1, GameAction::Skip { ship_id: 0 }).unwrap();
+        let session = apply(&session, &PLAYER2, GameAction::Skip { ship_id: 1 }).unwrap();
+
+        assert_eq!(session.turn, 2);
+        assert_eq!(session.phase, GamePhase::planning());
+        assert_eq!(session.player1_scrap, 8);
+        assert_eq!(session.player2_scrap, 8);
+    }
+
+    #[test]
+    fn hits_salvage_scrap() {
+        let mut session = duel_in_action();
+        session.ships[0].weapons[0].hit_chance = 10.0;
+
+        let session = apply(
+            &session,
+            &PLAYER1,
+            GameAction::FireWeapon {
+                ship_id: 0,
+                mount_index: 0,
+                target_id: 1,
+            },
+        )
+        .unwrap();
+        assert_eq!(session.player1_scrap, 6);
+    }
+
+    #[test]
+    fn ships_cant_shoot_wrecks_or_themselves() {
+        let mut session = duel_in_action();
+        let fire = |target_id| GameAction::FireWeapon {
+            ship_id: 0,
+            mount_index: 0,
+            target_id,
+        };
+
+        let result = apply(&session, &PLAYER1, fire(0));
+        assert!(matches!(result, Err(GameError::InvalidTarget)));
+
+        session.ships[1].health = 0.0;
+        let result = apply(&session, &PLAYER1, fire(1));
+        assert!(matches!(result, Err(GameError::InvalidTarget)));
+    }
+
+    #[test]
+    fn hits_record_the_shield_and_hull_damage() {
+        let mut session = duel_in_action();
+        session.ships[0].weapons[0].hit_chance = 10.0;
+
+        let session = apply(
+            &session,
+            &PLAYER1,
+            GameAction::FireWeapon {
+                ship_id: 0,
+                mount_index: 0,
+                target_id: 1,
+            },
+        )
+        .unwrap();
+
+        // The full shields soak up the first 20 damage, the rest goes through the armor
+        assert_eq!(session.ships[1].shields, 0.0);
+        let hull_damage = 100.0 - session.ships[1].health;
+        assert!(hull_damage > 0.0);
+        assert_eq!(
+            session.last_action_data,
+            vec![0, 1, 0, 20, hull_damage.round() as u32]
+        );
+    }
+
+    #[test]
+    fn every_roll_of_a_shot_is_kept() {
+        let mut session = duel(SessionRules {
+            critical_chance: 1.0,
+            ..SessionRules::default()
+        });
+        session = plan(&session, &[(0, straight(20.0)), (1, straight(20.0))]);
+        session.ships[0].weapons[0].hit_chance = 10.0;
+        let nonce = session.roll_nonce;
+
+        // Hit, critical and the damaged system
+        let session = apply(
+            &session,
+            &PLAYER1,
+            GameAction::FireWeapon {
+                ship_id: 0,
+                mount_index: 0,
+                target_id: 1,
+            },
+        )
+        .unwrap();
+        let nonces: Vec<u64> = session.last_rolls.iter().map(|r| r.nonce).collect();
+        assert_eq!(nonces, vec![nonce, nonce + 1, nonce + 2]);
+        assert!(session.last_rolls.iter().all(|r| r.subject == [0, 1, 0]));
+
+        // Actions without rolls leave nothing behind
+        let session = apply(&session, &PLAYER2, GameAction::Skip { ship_id: 1 }).unwrap();
+        assert!(session.last_rolls.is_empty());
+    }
+
+    #[test]
+    fn shot_breakdown_is_kept_on_the_session() {
+        let session = duel_in_action();
+        let (session, result) = session
+            .apply(
+                &context(&PLAYER1, 0),
+                GameAction::FireWeapon {
+                    ship_id: 0,
+                    mount_index: 0,
+                    target_id: 1,
+                },
+            )
+            .unwrap();
+        let shot = match result {
+            ActionResult::WeaponFired { shot, .. } => shot,
+            _ => panic!("Expected a shot"),
+        };
+        assert_eq!(session.last_shot, Some(shot));
+
+        // Clients read it back from the account data
+        let data = session.try_to_vec().unwrap();
+        let stored = GameSession::try_from_slice(&data).unwrap();
+        assert_eq!(stored.last_shot, session.last_shot);
+
+        let session = apply(&session, &PLAYER2, GameAction::Skip { ship_id: 1 }).unwrap();
+        assert_eq!(session.last_shot, None);
+    }
+}
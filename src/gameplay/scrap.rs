@@ -0,0 +1,36 @@
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::seat::Seat;
+use solana_program::msg;
+
+impl GameSession {
+    pub fn scrap(&self, seat: Seat) -> u32 {
+        match seat {
+            Seat::Player1 => self.player1_scrap,
+            Seat::Player2 => self.player2_scrap,
+        }
+    }
+
+    fn scrap_mut(&mut self, seat: Seat) -> &mut u32 {
+        match seat {
+            Seat::Player1 => &mut self.player1_scrap,
+            Seat::Player2 => &mut self.player2_scrap,
+        }
+    }
+
+    pub fn earn_scrap(&mut self, seat: Seat, amount: u32) {
+        let scrap = self.scrap_mut(seat);
+        *scrap = scrap.saturating_add(amount);
+    }
+
+    pub fn spend_scrap(&mut self, seat: Seat, amount: u32) -> Result<(), GameError> {
+        let scrap = self.scrap_mut(seat);
+        if *scrap < amount {
+            msg!("Need {} scrap but only have {}", amount, *scrap);
+            return Err(GameError::NotEnoughScrap);
+        }
+
+        *scrap -= amount;
+        Ok(())
+    }
+}
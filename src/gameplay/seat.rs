@@ -0,0 +1,66 @@
+use crate::error::GameError;
+use crate::gameplay::game_session::GameSession;
+use solana_program::{msg, pubkey::Pubkey};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Seat {
+    Player1,
+    Player2,
+}
+
+impl Seat {
+    pub fn opponent(&self) -> Seat {
+        match self {
+            Seat::Player1 => Seat::Player2,
+            Seat::Player2 => Seat::Player1,
+        }
+    }
+}
+
+impl GameSession {
+    pub fn player(&self, seat: Seat) -> Pubkey {
+        match seat {
+            Seat::Player1 => self.player1,
+            Seat::Player2 => self.player2,
+        }
+    }
+
+    // Resolve a signer to their seat, anyone who isn't playing in this session is rejected
+    pub fn seat_of(&self, player: &Pubkey) -> Result<Seat, GameError> {
+        if *player == Pubkey::default() {
+            msg!("Session has no players yet");
+            return Err(GameError::NotAPlayer);
+        }
+
+        if *player == self.player1 {
+            Ok(Seat::Player1)
+        } else if *player == self.player2 {
+            Ok(Seat::Player2)
+        } else {
+            msg!("{} is not a player in this session", player);
+            Err(GameError::NotAPlayer)
+        }
+    }
+
+    // Shared guard for every session instruction, also checks the signer owns every ship it touches
+    pub fn authorize(&self, player: &Pubkey, ship_ids: &[u32]) -> Result<Seat, GameError> {
+        let seat = self.seat_of(player)?;
+
+        for ship_id in ship_ids {
+            let ship = match self.ships.get(*ship_id as usize) {
+                Some(ship) => ship,
+                None => {
+                    msg!("Invalid ship id {}", ship_id);
+                    return Err(GameError::InvalidShip);
+                }
+            };
+
+            if ship.owner != *player {
+                msg!("Ship {} does not belong to {}", ship_id, player);
+                return Err(GameError::NotShipOwner);
+            }
+        }
+
+        Ok(seat)
+    }
+}
@@ -0,0 +1,215 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::error::GameError;
+use crate::gameplay::arena::{self, ArenaShape, OutOfBoundsRule};
+use crate::gameplay::phase::GamePhase;
+use solana_program::msg;
+
+// What happens to a maneuver that breaks the ship's limits or is missing entirely
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum InvalidManeuverRule {
+    // The whole reveal is rejected
+    Reject,
+    // The ship flies its default maneuver instead
+    UseDefault,
+}
+
+// What happens to a deployment placement outside the player's zone or touching something
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum InvalidPlacementRule {
+    // The whole reveal is rejected
+    Reject,
+    // The ship keeps its spot in the starting line
+    KeepStartingLine,
+}
+
+// What happens to a player who lets a deadline pass
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TimeoutPenalty {
+    // The game moves on without them: activations are skipped and ships fly their default maneuver
+    AutoResolve,
+    // The player loses right away
+    Forfeit,
+}
+
+// Rules that are fixed when a session is created
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct SessionRules {
+    pub invalid_maneuver_rule: InvalidManeuverRule,
+    pub invalid_placement_rule: InvalidPlacementRule,
+
+    // Seconds a player gets for each phase, or for each activation during the action phase
+    pub deployment_timeout: i64,
+    pub planning_timeout: i64,
+    pub reveal_timeout: i64,
+    pub activation_timeout: i64,
+
+    pub timeout_penalty: TimeoutPenalty,
+    // Timeouts after which a player forfeits, 0 means never
+    pub max_strikes: u8,
+
+    // Chess clock, total seconds per player plus seconds gained per action. A time bank of 0 turns it off
+    pub time_bank: i64,
+    pub time_increment: i64,
+
+    // Playing area, it has to contain both deployment zones
+    pub arena: ArenaShape,
+    // What happens to ships outside the arena, and the damage they take each turn with DamagePerTurn
+    pub out_of_bounds_rule: OutOfBoundsRule,
+    pub out_of_bounds_damage: f32,
+
+    // Closing storm, starting at storm_start_turn the arena shrinks by storm_shrink of its size every
+    // storm_interval turns, down to storm_min_scale. Ships outside take storm_damage times the number
+    // of shrinks so far at the end of each turn. A start turn of 0 turns it off
+    pub storm_start_turn: u8,
+    pub storm_interval: u8,
+    pub storm_shrink: f32,
+    pub storm_min_scale: f32,
+    pub storm_damage: f32,
+
+    // Scrap for upgrades, players get some at the start and every turn, and salvage more by hitting
+    // and destroying enemy ships
+    pub starting_scrap: u32,
+    pub scrap_per_turn: u32,
+    pub scrap_per_hit: u32,
+    pub scrap_per_kill: u32,
+
+    // Health a repair restores, damage multiplier for braced ships and hit chance multiplier against
+    // evading ships
+    pub repair_amount: f32,
+    pub brace_damage_modifier: f32,
+    pub evade_hit_modifier: f32,
+    // Chance a hit that damages the hull also knocks out one of the ship's systems
+    pub critical_chance: f32,
+
+    // Damage a ship takes when it ends its maneuver inside an obstacle
+    pub obstacle_collision_damage: f32,
+    // Damage a ship takes when rammed, plus extra for every unit of speed the other ship had
+    pub ram_damage: f32,
+    pub ram_damage_per_speed: f32,
+}
+
+impl SessionRules {
+    pub fn timeout_for(&self, phase: &GamePhase) -> Option<i64> {
+        match phase {
+            GamePhase::Deployment { .. } => Some(self.deployment_timeout),
+            GamePhase::Planning { .. } => Some(self.planning_timeout),
+            GamePhase::DeploymentReveal { .. } | GamePhase::Reveal { .. } => {
+                Some(self.reveal_timeout)
+            }
+            GamePhase::Action => Some(self.activation_timeout),
+            GamePhase::Lobby | GamePhase::Finished => None,
+        }
+    }
+
+    // Rules are picked by the players joining the queue, so anything that could break a session is rejected
+    pub fn validate(&self) -> Result<(), GameError> {
+        let timeouts = [
+            self.deployment_timeout,
+            self.planning_timeout,
+            self.reveal_timeout,
+            self.activation_timeout,
+        ];
+        if timeouts.iter().any(|x| *x <= 0) {
+            msg!("Every phase needs a timeout of at least one second");
+            return Err(GameError::InvalidRules);
+        }
+
+        if self.time_bank < 0 || self.time_increment < 0 {
+            msg!("Time bank and increment can't be negative");
+            return Err(GameError::InvalidRules);
+        }
+
+        // Without a time bank the clock is off, so an increment would never be used
+        if self.time_bank == 0 && self.time_increment != 0 {
+            msg!("Time increment needs a time bank");
+            return Err(GameError::InvalidRules);
+        }
+
+        self.arena.validate()?;
+
+        if self.storm_start_turn > 0 {
+            if self.storm_interval == 0 {
+                msg!("Storm needs an interval of at least one turn");
+                return Err(GameError::InvalidRules);
+            }
+
+            // The arena can never shrink away entirely
+            if !(0.0..=1.0).contains(&self.storm_shrink)
+                || !(0.0..=1.0).contains(&self.storm_min_scale)
+                || self.storm_min_scale == 0.0
+            {
+                msg!("Storm shrink has to be between 0 and 1 and its minimum scale above 0");
+                return Err(GameError::InvalidRules);
+            }
+        }
+
+        let amounts = [
+            self.out_of_bounds_damage,
+            self.storm_damage,
+            self.repair_amount,
+            self.obstacle_collision_damage,
+            self.ram_damage,
+            self.ram_damage_per_speed,
+        ];
+        if amounts.iter().any(|x| !x.is_finite() || *x < 0.0) {
+            msg!("Damage and repair amounts can't be negative");
+            return Err(GameError::InvalidRules);
+        }
+
+        let multipliers = [
+            self.brace_damage_modifier,
+            self.evade_hit_modifier,
+            self.critical_chance,
+        ];
+        if multipliers.iter().any(|x| !(0.0..=1.0).contains(x)) {
+            msg!("Modifiers and chances have to be between 0 and 1");
+            return Err(GameError::InvalidRules);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SessionRules {
+    fn default() -> Self {
+        SessionRules {
+            invalid_maneuver_rule: InvalidManeuverRule::UseDefault,
+            invalid_placement_rule: InvalidPlacementRule::KeepStartingLine,
+
+            deployment_timeout: 120,
+            planning_timeout: 120,
+            reveal_timeout: 60,
+            activation_timeout: 60,
+
+            timeout_penalty: TimeoutPenalty::AutoResolve,
+            max_strikes: 3,
+
+            time_bank: 0,
+            time_increment: 0,
+
+            arena: arena::default_arena(),
+            out_of_bounds_rule: OutOfBoundsRule::Destroy,
+            out_of_bounds_damage: 25.0,
+
+            storm_start_turn: 0,
+            storm_interval: 2,
+            storm_shrink: 0.1,
+            storm_min_scale: 0.3,
+            storm_damage: 10.0,
+
+            starting_scrap: 5,
+            scrap_per_turn: 3,
+            scrap_per_hit: 1,
+            scrap_per_kill: 5,
+
+            repair_amount: 25.0,
+            brace_damage_modifier: 0.5,
+            evade_hit_modifier: 0.6,
+            critical_chance: 0.15,
+
+            obstacle_collision_damage: 20.0,
+            ram_damage: 10.0,
+            ram_damage_per_speed: 1.5,
+        }
+    }
+}
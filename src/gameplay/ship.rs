@@ -1,14 +1,31 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use crate::error::GameError;
+use crate::gameplay::critical::DamagedSystems;
+use crate::gameplay::damage::DamageType;
+use crate::gameplay::ship_class::ShipClass;
+use crate::gameplay::upgrade::UPGRADE_COUNT;
+use crate::utils::vec2::Vector2;
+use solana_program::{msg, pubkey::Pubkey};
+use std::f32::consts::PI;
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Copy)]
+// Radius of the circle ships occupy for collisions
+pub const SHIP_RADIUS: f32 = 2.0;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub struct Ship {
     pub id: u32,
     pub owner: Pubkey,
+    pub class: ShipClass,
     pub x_position: f32,
     pub y_position: f32,
     pub rotation: f32,
     pub health: f32,
+    pub max_health: f32,
+    // Shields soak up hits before the hull and come back every turn, armor blunts every hit on the hull
+    pub shields: f32,
+    pub max_shields: f32,
+    pub shield_regen: f32,
+    pub armor: f32,
 
     pub min_speed: f32,
     pub max_speed: f32,
@@ -16,20 +33,171 @@ pub struct Ship {
     pub max_angle: f32,
 
     pub maneuver_data: ManeuverData,
-    pub weapon_data: WeaponData,
+    pub weapons: Vec<WeaponData>,
+    // Times each upgrade in the catalog has been installed
+    pub upgrade_stacks: [u8; UPGRADE_COUNT],
     pub activated: bool,
+    // Defensive stances, they last until the end of the turn
+    pub braced: bool,
+    pub evading: bool,
+    // Systems knocked out by critical hits
+    pub damaged_systems: DamagedSystems,
+}
+
+impl Ship {
+    pub fn position(&self) -> Vector2 {
+        Vector2 {
+            x: self.x_position,
+            y: self.y_position,
+        }
+    }
+
+    pub fn forward(&self) -> Vector2 {
+        let rot_rad = self.rotation.to_radians();
+        Vector2 {
+            x: rot_rad.cos(),
+            y: rot_rad.sin(),
+        }
+    }
+
+    pub fn weapon(&self, mount_index: u32) -> Result<WeaponData, GameError> {
+        match self.weapons.get(mount_index as usize) {
+            Some(weapon) => Ok(*weapon),
+            None => {
+                msg!("Ship {} has no weapon mount {}", self.id, mount_index);
+                Err(GameError::InvalidWeapon)
+            }
+        }
+    }
+
+    // Direction the centre of a weapon's arc points in
+    pub fn weapon_direction(&self, weapon: &WeaponData) -> Vector2 {
+        let rot_rad = (self.rotation + weapon.facing).to_radians();
+        Vector2 {
+            x: rot_rad.cos(),
+            y: rot_rad.sin(),
+        }
+    }
+
+    // Whether the ship can fly this maneuver with its current limits
+    pub fn is_valid_maneuver(&self, maneuver: &ManeuverData) -> bool {
+        if !maneuver.speed.is_finite()
+            || !maneuver.angle.is_finite()
+            || !maneuver.turret_facing.is_finite()
+        {
+            return false;
+        }
+
+        let angle = maneuver.angle.abs();
+        maneuver.speed >= self.min_speed
+            && maneuver.speed <= self.effective_max_speed()
+            && angle >= self.min_angle
+            && angle <= self.effective_max_angle()
+    }
+
+    // Slowest straight maneuver the ship can fly, with the turrets staying where they are
+    pub fn default_maneuver(&self) -> ManeuverData {
+        ManeuverData {
+            angle: self.min_angle,
+            speed: self.min_speed,
+            turret_facing: self
+                .weapons
+                .iter()
+                .find(|w| w.is_turret())
+                .map_or(0.0, |w| w.facing),
+        }
+    }
+
+    // Turn every turret towards the planned facing, as far as its traverse allows this turn
+    pub fn aim_turrets(&mut self) {
+        let target = self.maneuver_data.turret_facing;
+        for weapon in self.weapons.iter_mut().filter(|w| w.is_turret()) {
+            let difference = (target - weapon.facing + 180.0).rem_euclid(360.0) - 180.0;
+            let turn = difference.clamp(-weapon.traverse, weapon.traverse);
+            weapon.facing = (weapon.facing + turn + 180.0).rem_euclid(360.0) - 180.0;
+        }
+    }
+
+    // Position and rotation after flying part of the maneuver, t goes from 0 (start) to 1 (end)
+    pub fn pose_along_maneuver(&self, t: f32) -> (Vector2, f32) {
+        // Straight maneuver
+        if self.maneuver_data.angle == 0.0 {
+            let forward = self.forward();
+
+            // Calculate the change in x and y based on the speed and angle
+            let delta_x = self.maneuver_data.speed * t * forward.x;
+            let delta_y = self.maneuver_data.speed * t * forward.y;
+
+            // Apply the relative movement based on the ship's rotation
+            let position = Vector2 {
+                x: self.x_position + delta_x,
+                y: self.y_position + delta_y,
+            };
+            (position, self.rotation)
+        }
+        // Arc maneuver
+        else {
+            let radius = self.maneuver_data.speed / (self.maneuver_data.angle / 8.0 * PI);
+            let arc = -(self.maneuver_data.speed / (2.0 * PI * radius) * 360.0) * t;
+
+            let rot_rad = (self.rotation + 90.0).to_radians();
+            let right_x = rot_rad.cos();
+            let right_y = rot_rad.sin();
+
+            let centre_x = self.x_position - right_x * radius;
+            let centre_y = self.y_position - right_y * radius;
+
+            let arm_x = self.x_position - centre_x;
+            let arm_y = self.y_position - centre_y;
+
+            let arc_rad = arc.to_radians();
+            let final_arm_x = arm_x * arc_rad.cos() - arm_y * arc_rad.sin();
+            let final_arm_y = arm_x * arc_rad.sin() + arm_y * arc_rad.cos();
+
+            let position = Vector2 {
+                x: centre_x + final_arm_x,
+                y: centre_y + final_arm_y,
+            };
+            (position, self.rotation + arc)
+        }
+    }
+
+    pub fn set_pose(&mut self, position: &Vector2, rotation: f32) {
+        self.x_position = position.x;
+        self.y_position = position.y;
+        self.rotation = rotation;
+    }
+
+    // Whether the point lies within the given number of degrees of the bow
+    pub fn is_facing(&self, point: &Vector2, arc: f32) -> bool {
+        let delta = point.subtract(&self.position());
+        self.forward().angle_between(&delta).to_degrees() <= arc
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Clone, Copy)]
 pub struct ManeuverData {
     pub angle: f32,
     pub speed: f32,
+    // Degrees from the bow the ship's turrets should point at after moving
+    pub turret_facing: f32,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Clone, Copy)]
 pub struct WeaponData {
+    // Degrees the mount points away from the bow, positive is towards port
+    pub facing: f32,
+    // Degrees a turret can turn each turn, 0 for a fixed mount
+    pub traverse: f32,
     pub arc: f32,
     pub range: f32,
     pub damage: f32,
     pub hit_chance: f32,
+    pub damage_type: DamageType,
+}
+
+impl WeaponData {
+    pub fn is_turret(&self) -> bool {
+        self.traverse > 0.0
+    }
 }
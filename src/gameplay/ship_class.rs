@@ -0,0 +1,190 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::gameplay::critical::DamagedSystems;
+use crate::gameplay::damage::DamageType;
+use crate::gameplay::ship::{ManeuverData, Ship, WeaponData};
+use crate::gameplay::upgrade::UPGRADE_COUNT;
+use crate::utils::vec2::Vector2;
+use solana_program::pubkey::Pubkey;
+
+// Catalog of ships a player can bring to a match
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ShipClass {
+    // All-rounder with a bow cannon and broadsides
+    Longship,
+    // Fast and nimble, but fragile and short ranged, with axes fore and aft
+    Raider,
+    // Slow and hard to turn, but tough and hits hard from far away, with a bow turret and heavy broadsides
+    Dreadnought,
+}
+
+// Starting stats of a class
+pub struct ClassStats {
+    pub health: f32,
+    pub shields: f32,
+    pub shield_regen: f32,
+    pub armor: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub min_angle: f32,
+    pub max_angle: f32,
+    pub weapons: Vec<WeaponData>,
+}
+
+impl ShipClass {
+    // Fleet points it costs to bring the ship to a match
+    pub fn cost(&self) -> u32 {
+        match self {
+            ShipClass::Longship => 30,
+            ShipClass::Raider => 20,
+            ShipClass::Dreadnought => 50,
+        }
+    }
+
+    pub fn stats(&self) -> ClassStats {
+        match self {
+            ShipClass::Longship => ClassStats {
+                health: 100.0,
+                shields: 20.0,
+                shield_regen: 10.0,
+                armor: 5.0,
+                min_speed: 10.0,
+                max_speed: 20.0,
+                min_angle: 0.0,
+                max_angle: 90.0,
+                weapons: vec![
+                    WeaponData {
+                        facing: 0.0,
+                        traverse: 0.0,
+                        arc: 45.0,
+                        range: 30.0,
+                        damage: 40.0,
+                        hit_chance: 0.7,
+                        damage_type: DamageType::Cannon,
+                    },
+                    WeaponData {
+                        facing: 90.0,
+                        traverse: 0.0,
+                        arc: 60.0,
+                        range: 20.0,
+                        damage: 25.0,
+                        hit_chance: 0.7,
+                        damage_type: DamageType::Cannon,
+                    },
+                    WeaponData {
+                        facing: -90.0,
+                        traverse: 0.0,
+                        arc: 60.0,
+                        range: 20.0,
+                        damage: 25.0,
+                        hit_chance: 0.7,
+                        damage_type: DamageType::Cannon,
+                    },
+                ],
+            },
+            ShipClass::Raider => ClassStats {
+                health: 70.0,
+                shields: 10.0,
+                shield_regen: 5.0,
+                armor: 0.0,
+                min_speed: 14.0,
+                max_speed: 28.0,
+                min_angle: 0.0,
+                max_angle: 120.0,
+                weapons: vec![
+                    WeaponData {
+                        facing: 0.0,
+                        traverse: 0.0,
+                        arc: 60.0,
+                        range: 25.0,
+                        damage: 30.0,
+                        hit_chance: 0.75,
+                        damage_type: DamageType::Axe,
+                    },
+                    WeaponData {
+                        facing: 180.0,
+                        traverse: 0.0,
+                        arc: 45.0,
+                        range: 15.0,
+                        damage: 20.0,
+                        hit_chance: 0.6,
+                        damage_type: DamageType::Axe,
+                    },
+                ],
+            },
+            ShipClass::Dreadnought => ClassStats {
+                health: 160.0,
+                shields: 40.0,
+                shield_regen: 10.0,
+                armor: 10.0,
+                min_speed: 6.0,
+                max_speed: 14.0,
+                min_angle: 0.0,
+                max_angle: 60.0,
+                weapons: vec![
+                    WeaponData {
+                        facing: 0.0,
+                        traverse: 90.0,
+                        arc: 30.0,
+                        range: 40.0,
+                        damage: 55.0,
+                        hit_chance: 0.6,
+                        damage_type: DamageType::Harpoon,
+                    },
+                    WeaponData {
+                        facing: 90.0,
+                        traverse: 0.0,
+                        arc: 60.0,
+                        range: 30.0,
+                        damage: 35.0,
+                        hit_chance: 0.65,
+                        damage_type: DamageType::Cannon,
+                    },
+                    WeaponData {
+                        facing: -90.0,
+                        traverse: 0.0,
+                        arc: 60.0,
+                        range: 30.0,
+                        damage: 35.0,
+                        hit_chance: 0.65,
+                        damage_type: DamageType::Cannon,
+                    },
+                ],
+            },
+        }
+    }
+
+    // Build a fresh ship of this class
+    pub fn build(&self, id: u32, owner: Pubkey, position: &Vector2, rotation: f32) -> Ship {
+        let stats = self.stats();
+
+        Ship {
+            id,
+            owner,
+            class: *self,
+            x_position: position.x,
+            y_position: position.y,
+            rotation,
+            health: stats.health,
+            max_health: stats.health,
+            shields: stats.shields,
+            max_shields: stats.shields,
+            shield_regen: stats.shield_regen,
+            armor: stats.armor,
+            min_speed: stats.min_speed,
+            max_speed: stats.max_speed,
+            min_angle: stats.min_angle,
+            max_angle: stats.max_angle,
+            maneuver_data: ManeuverData {
+                angle: 0.0,
+                speed: 0.0,
+                turret_facing: 0.0,
+            },
+            weapons: stats.weapons,
+            upgrade_stacks: [0; UPGRADE_COUNT],
+            activated: false,
+            braced: false,
+            evading: false,
+            damaged_systems: DamagedSystems::none(),
+        }
+    }
+}
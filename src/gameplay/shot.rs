@@ -0,0 +1,147 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::gameplay::critical::DAMAGED_WEAPONS_DAMAGE;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::line_of_sight::LineOfSight;
+use crate::gameplay::ship::{Ship, WeaponData};
+use crate::utils::vec2::Vector2;
+use solana_program::msg;
+
+// Targets within this fraction of a weapon's range are at close range, beyond the long one at long range
+pub const CLOSE_RANGE: f32 = 1.0 / 3.0;
+pub const LONG_RANGE: f32 = 2.0 / 3.0;
+
+// Hit chance lost at the very edge of a weapon's arc compared to dead ahead
+pub const ARC_EDGE_PENALTY: f32 = 0.3;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ModifierSource {
+    CloseRange,
+    LongRange,
+    OffBoresight,
+    Upgrades,
+    DamagedWeapons,
+    Cover,
+    Evasion,
+    Brace,
+}
+
+// Multipliers one source applies to the hit chance and damage
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Modifier {
+    pub source: ModifierSource,
+    pub hit_chance: f32,
+    pub damage: f32,
+}
+
+// Every step from the weapon's base numbers to the ones the roll is made against
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct ShotBreakdown {
+    pub base_hit_chance: f32,
+    pub base_damage: f32,
+    pub modifiers: Vec<Modifier>,
+    pub hit_chance: f32,
+    pub damage: f32,
+    pub cover: f32,
+}
+
+impl ShotBreakdown {
+    fn apply(&mut self, source: ModifierSource, hit_chance: f32, damage: f32) {
+        if hit_chance == 1.0 && damage == 1.0 {
+            return;
+        }
+
+        self.modifiers.push(Modifier {
+            source,
+            hit_chance,
+            damage,
+        });
+        self.hit_chance *= hit_chance;
+        self.damage *= damage;
+    }
+}
+
+impl GameSession {
+    // Run a shot through the modifier pipeline, target_ship is None for obstacles
+    pub fn shot_breakdown(
+        &self,
+        ship: &Ship,
+        mount_index: u32,
+        weapon: &WeaponData,
+        target_pos: &Vector2,
+        sight: &LineOfSight,
+        target_ship: Option<&Ship>,
+    ) -> ShotBreakdown {
+        // Base numbers come from the ship class, so whatever upgrades changed shows up as its own step
+        let base = ship
+            .class
+            .stats()
+            .weapons
+            .get(mount_index as usize)
+            .copied()
+            .unwrap_or(*weapon);
+
+        let mut shot = ShotBreakdown {
+            base_hit_chance: base.hit_chance,
+            base_damage: base.damage,
+            modifiers: Vec::new(),
+            hit_chance: base.hit_chance,
+            damage: base.damage,
+            cover: sight.cover(),
+        };
+
+        if base.hit_chance > 0.0 && base.damage > 0.0 {
+            shot.apply(
+                ModifierSource::Upgrades,
+                weapon.hit_chance / base.hit_chance,
+                weapon.damage / base.damage,
+            );
+        }
+
+        if ship.damaged_systems.weapons {
+            shot.apply(ModifierSource::DamagedWeapons, 1.0, DAMAGED_WEAPONS_DAMAGE);
+        }
+
+        // Close shots are more accurate and hit harder, long shots the opposite
+        let delta = target_pos.subtract(&ship.position());
+        let range_fraction = delta.magnitude() / weapon.range.max(f32::EPSILON);
+        if range_fraction <= CLOSE_RANGE {
+            shot.apply(ModifierSource::CloseRange, 1.1, 1.2);
+        } else if range_fraction > LONG_RANGE {
+            shot.apply(ModifierSource::LongRange, 0.8, 0.75);
+        }
+
+        // The further from the centre of the arc, the harder it is to aim
+        let angle = ship
+            .weapon_direction(weapon)
+            .angle_between(&delta)
+            .to_degrees();
+        let arc_fraction = (angle.abs() / ship.effective_arc(weapon).max(f32::EPSILON)).min(1.0);
+        shot.apply(
+            ModifierSource::OffBoresight,
+            1.0 - ARC_EDGE_PENALTY * arc_fraction,
+            1.0,
+        );
+
+        shot.apply(ModifierSource::Cover, sight.cover_modifier(), 1.0);
+
+        if let Some(target) = target_ship {
+            if target.evading {
+                shot.apply(ModifierSource::Evasion, self.rules.evade_hit_modifier, 1.0);
+            }
+            if target.braced {
+                shot.apply(ModifierSource::Brace, 1.0, self.rules.brace_damage_modifier);
+            }
+        }
+
+        shot.hit_chance = shot.hit_chance.clamp(0.0, 1.0);
+
+        // Logged so clients can explain the roll
+        msg!(
+            "Hit chance {} and damage {} after modifiers {:?}",
+            shot.hit_chance,
+            shot.damage,
+            shot.modifiers
+        );
+        shot
+    }
+}
@@ -0,0 +1,103 @@
+use crate::gameplay::arena::ArenaShape;
+use crate::gameplay::game_session::GameSession;
+use solana_program::msg;
+
+impl GameSession {
+    // How many times the storm has closed in by the given turn, 0 while it hasn't started
+    pub fn storm_stage(&self, turn: u8) -> u8 {
+        let rules = &self.rules;
+        if rules.storm_start_turn == 0 || turn < rules.storm_start_turn {
+            return 0;
+        }
+
+        (turn - rules.storm_start_turn) / rules.storm_interval.max(1) + 1
+    }
+
+    // The part of the arena the storm hasn't reached yet
+    pub fn storm_arena(&self) -> ArenaShape {
+        let stage = self.storm_stage(self.turn) as f32;
+        let scale = (1.0 - self.rules.storm_shrink * stage).max(self.rules.storm_min_scale);
+        self.rules.arena.scaled(scale)
+    }
+
+    // Damage every ship caught in the storm at the end of the turn, it gets worse every stage
+    pub fn apply_storm(&mut self) {
+        let stage = self.storm_stage(self.turn);
+        if stage == 0 {
+            return;
+        }
+
+        let safe_area = self.storm_arena();
+        let damage = self.rules.storm_damage * stage as f32;
+        for ship in self.ships.iter_mut() {
+            if ship.health <= 0.0 || safe_area.contains(&ship.position()) {
+                continue;
+            }
+
+            msg!("Ship {} took {} storm damage", ship.id, damage);
+            ship.health -= damage;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::test_session::*;
+
+    // Storm starts on turn 3 and closes in by a quarter every other turn, down to 40% of the arena
+    fn stormy_duel() -> GameSession {
+        duel(SessionRules {
+            storm_start_turn: 3,
+            storm_interval: 2,
+            storm_shrink: 0.25,
+            storm_min_scale: 0.4,
+            storm_damage: 10.0,
+            ..SessionRules::default()
+        })
+    }
+
+    #[test]
+    fn storm_closes_in_every_interval_after_it_starts() {
+        let session = stormy_duel();
+        let stages: Vec<u8> = (1..=7).map(|turn| session.storm_stage(turn)).collect();
+        assert_eq!(stages, vec![0, 0, 1, 1, 2, 2, 3]);
+
+        // A start turn of 0 turns the storm off
+        let session = duel(SessionRules::default());
+        assert!((1..=50).all(|turn| session.storm_stage(turn) == 0));
+    }
+
+    #[test]
+    fn storm_stops_shrinking_at_the_minimum_scale() {
+        let mut session = stormy_duel();
+        session.turn = 5;
+        assert_eq!(session.storm_arena(), session.rules.arena.scaled(0.5));
+
+        session.turn = 20;
+        assert_eq!(session.storm_arena(), session.rules.arena.scaled(0.4));
+    }
+
+    #[test]
+    fn storm_damage_grows_every_stage() {
+        let mut session = stormy_duel();
+        place(&mut session, 0, 0.0, -60.0, 90.0);
+        place(&mut session, 1, 0.0, 0.0, -90.0);
+
+        session.turn = 2;
+        session.apply_storm();
+        assert_eq!(session.ships[0].health, 100.0);
+
+        session.turn = 3;
+        session.apply_storm();
+        assert_eq!(session.ships[0].health, 90.0);
+
+        session.turn = 5;
+        session.apply_storm();
+        assert_eq!(session.ships[0].health, 70.0);
+
+        // Ships in the eye of the storm are safe
+        assert_eq!(session.ships[1].health, 100.0);
+    }
+}
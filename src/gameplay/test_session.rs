@@ -0,0 +1,220 @@
+use borsh::BorshSerialize;
+use crate::error::GameError;
+use crate::gameplay::deployment::Placement;
+use crate::gameplay::fleet::ShipSpec;
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::phase::GamePhase;
+use crate::gameplay::rules::{ActionContext, GameAction};
+use crate::gameplay::seat::Seat;
+use crate::gameplay::session_rules::SessionRules;
+use crate::gameplay::ship::ManeuverData;
+use crate::gameplay::ship_class::ShipClass;
+use crate::utils::commitment;
+use crate::utils::dictionary::Dictionary;
+use solana_program::pubkey::Pubkey;
+
+// Shared setup for the rules tests, everything goes through GameSession::apply like the instructions do
+
+pub const PLAYER1: Pubkey = Pubkey::new_from_array([1; 32]);
+pub const PLAYER2: Pubkey = Pubkey::new_from_array([2; 32]);
+pub const OUTSIDER: Pubkey = Pubkey::new_from_array([3; 32]);
+
+// Session in the planning phase of turn 1, deployed with both fleets in their starting lines and
+// no obstacles
+pub fn new_game(
+    player1_fleet: &[ShipClass],
+    player2_fleet: &[ShipClass],
+    rules: SessionRules,
+) -> GameSession {
+    let session = new_deployment(player1_fleet, player2_fleet, rules);
+    deploy(&session, &Dictionary::new(), &Dictionary::new())
+}
+
+// Session waiting for both players to commit their placements
+pub fn new_deployment(
+    player1_fleet: &[ShipClass],
+    player2_fleet: &[ShipClass],
+    rules: SessionRules,
+) -> GameSession {
+    let mut session = GameSession::new(255, 1, PLAYER1, [7; 32]);
+    session.rules = rules;
+    session.player1 = PLAYER1;
+    session.player2 = PLAYER2;
+    session.active_player = PLAYER1;
+    session.player1_time_left = session.rules.time_bank;
+    session.player2_time_left = session.rules.time_bank;
+    session.player1_scrap = session.rules.starting_scrap;
+    session.player2_scrap = session.rules.starting_scrap;
+
+    let fleet = |classes: &[ShipClass]| -> Vec<ShipSpec> {
+        classes
+            .iter()
+            .map(|class| ShipSpec {
+                class: *class,
+                upgrades: Vec::new(),
+            })
+            .collect()
+    };
+    session
+        .spawn_fleets(&fleet(player1_fleet), &fleet(player2_fleet))
+        .unwrap();
+    session.transition(GamePhase::deployment()).unwrap();
+    session
+}
+
+// Two longships with the given rules
+pub fn duel(rules: SessionRules) -> GameSession {
+    new_game(&[ShipClass::Longship], &[ShipClass::Longship], rules)
+}
+
+// Both longships close to 20 apart, well inside each other's bow cannon range
+pub fn duel_in_action() -> GameSession {
+    plan(
+        &duel(SessionRules::default()),
+        &[(0, straight(20.0)), (1, straight(20.0))],
+    )
+}
+
+pub fn context(player: &Pubkey, unix_timestamp: i64) -> ActionContext {
+    ActionContext {
+        player: *player,
+        unix_timestamp,
+        recent_slot_hash: [9; 32],
+    }
+}
+
+// Apply an action at the time of the last one
+pub fn apply(
+    session: &GameSession,
+    player: &Pubkey,
+    action: GameAction,
+) -> Result<GameSession, GameError> {
+    apply_at(session, player, action, session.updated_at)
+}
+
+pub fn apply_at(
+    session: &GameSession,
+    player: &Pubkey,
+    action: GameAction,
+    unix_timestamp: i64,
+) -> Result<GameSession, GameError> {
+    session
+        .apply(&context(player, unix_timestamp), action)
+        .map(|(session, _)| session)
+}
+
+pub fn straight(speed: f32) -> ManeuverData {
+    ManeuverData {
+        angle: 0.0,
+        speed,
+        turret_facing: 0.0,
+    }
+}
+
+pub fn place(session: &mut GameSession, ship_id: u32, x: f32, y: f32, rotation: f32) {
+    let ship = &mut session.ships[ship_id as usize];
+    ship.x_position = x;
+    ship.y_position = y;
+    ship.rotation = rotation;
+}
+
+pub fn salt(seat: Seat) -> [u8; 32] {
+    match seat {
+        Seat::Player1 => [11; 32],
+        Seat::Player2 => [22; 32],
+    }
+}
+
+// The maneuvers of one player, keyed by ship id
+pub fn maneuvers_for(
+    session: &GameSession,
+    player: &Pubkey,
+    maneuvers: &[(u32, ManeuverData)],
+) -> Dictionary<u32, ManeuverData> {
+    let mut owned = Dictionary::new();
+    for (ship_id, maneuver) in maneuvers {
+        if session.ships[*ship_id as usize].owner == *player {
+            owned.insert(*ship_id, *maneuver);
+        }
+    }
+    owned
+}
+
+pub fn placement_commitment(placements: &Dictionary<u32, Placement>, seat: Seat) -> [u8; 32] {
+    let data = placements.as_sorted_vec().try_to_vec().unwrap();
+    commitment::commit(&data, &salt(seat))
+}
+
+// Both players commit to and reveal the given placements, ships left out keep their starting spot
+pub fn deploy(
+    session: &GameSession,
+    player1_placements: &Dictionary<u32, Placement>,
+    player2_placements: &Dictionary<u32, Placement>,
+) -> GameSession {
+    let mut session = session.clone();
+    let players = [
+        (PLAYER1, Seat::Player1, player1_placements),
+        (PLAYER2, Seat::Player2, player2_placements),
+    ];
+
+    for (player, seat, placements) in players {
+        let commitment = placement_commitment(placements, seat);
+        session = apply(
+            &session,
+            &player,
+            GameAction::CommitDeployment { commitment },
+        )
+        .unwrap();
+    }
+
+    for (player, seat, placements) in players {
+        session = apply(
+            &session,
+            &player,
+            GameAction::RevealDeployment {
+                placements: placements.clone(),
+                salt: salt(seat),
+            },
+        )
+        .unwrap();
+    }
+
+    session
+}
+
+pub fn commitment_for(maneuvers: &Dictionary<u32, ManeuverData>, seat: Seat) -> [u8; 32] {
+    let data = maneuvers.as_sorted_vec().try_to_vec().unwrap();
+    commitment::commit(&data, &salt(seat))
+}
+
+// Both players commit to and reveal the given maneuvers, which resolves the movement
+pub fn plan(session: &GameSession, maneuvers: &[(u32, ManeuverData)]) -> GameSession {
+    let mut session = session.clone();
+    let players = [(PLAYER1, Seat::Player1), (PLAYER2, Seat::Player2)];
+
+    for (player, seat) in players {
+        let owned = maneuvers_for(&session, &player, maneuvers);
+        let commitment = commitment_for(&owned, seat);
+        session = apply(
+            &session,
+            &player,
+            GameAction::CommitManeuvers { commitment },
+        )
+        .unwrap();
+    }
+
+    for (player, seat) in players {
+        let maneuver_data = maneuvers_for(&session, &player, maneuvers);
+        session = apply(
+            &session,
+            &player,
+            GameAction::RevealManeuvers {
+                maneuver_data,
+                salt: salt(seat),
+            },
+        )
+        .unwrap();
+    }
+
+    session
+}
@@ -0,0 +1,172 @@
+use crate::gameplay::game_session::GameSession;
+use crate::gameplay::seat::Seat;
+
+impl GameSession {
+    pub fn has_time_bank(&self) -> bool {
+        self.rules.time_bank > 0
+    }
+
+    pub fn time_left(&self, seat: Seat) -> i64 {
+        match seat {
+            Seat::Player1 => self.player1_time_left,
+            Seat::Player2 => self.player2_time_left,
+        }
+    }
+
+    fn time_left_mut(&mut self, seat: Seat) -> &mut i64 {
+        match seat {
+            Seat::Player1 => &mut self.player1_time_left,
+            Seat::Player2 => &mut self.player2_time_left,
+        }
+    }
+
+    // Run the clock of everyone the game was waiting on since the last action
+    pub fn charge_clocks(&mut self, now: i64) {
+        if !self.has_time_bank() {
+            return;
+        }
+
+        let elapsed = now.saturating_sub(self.updated_at).max(0);
+        for seat in self.stalling_seats() {
+            let time_left = self.time_left_mut(seat);
+            *time_left = time_left.saturating_sub(elapsed);
+        }
+    }
+
+    pub fn add_increment(&mut self, seat: Seat) {
+        if !self.has_time_bank() {
+            return;
+        }
+
+        let increment = self.rules.time_increment;
+        let time_left = self.time_left_mut(seat);
+        *time_left = time_left.saturating_add(increment);
+    }
+
+    // Player whose clock ran out, if both did it's whoever is furthest over
+    pub fn flagged_seat(&self) -> Option<Seat> {
+        if !self.has_time_bank() {
+            return None;
+        }
+
+        self.stalling_seats()
+            .into_iter()
+            .filter(|seat| self.time_left(*seat) <= 0)
+            .min_by_key(|seat| self.time_left(*seat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::GameError;
+    use crate::gameplay::rules::{ActionResult, GameAction};
+    use crate::gameplay::session_rules::SessionRules;
+    use crate::gameplay::test_session::*;
+    use solana_program::pubkey::Pubkey;
+
+    // 100 seconds each with 5 more for every action
+    fn timed_duel() -> GameSession {
+        duel(SessionRules {
+            time_bank: 100,
+            time_increment: 5,
+            ..SessionRules::default()
+        })
+    }
+
+    fn commit_nothing(session: &GameSession, player: &Pubkey, seat: Seat, at: i64) -> GameSession {
+        let commitment = commitment_for(&maneuvers_for(session, player, &[]), seat);
+        apply_at(
+            session,
+            player,
+            GameAction::CommitManeuvers { commitment },
+            at,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn clocks_only_run_for_players_the_game_is_waiting_on() {
+        let session = timed_duel();
+        let start = session.updated_at;
+        let (player1_time, player2_time) = (session.player1_time_left, session.player2_time_left);
+
+        // Both players are planning, so both clocks run and the one who acted gets the increment
+        let session = commit_nothing(&session, &PLAYER1, Seat::Player1, start + 30);
+        assert_eq!(session.player1_time_left, player1_time - 30 + 5);
+        assert_eq!(session.player2_time_left, player2_time - 30);
+
+        // Player 1 is done and only waits for player 2
+        let session = commit_nothing(&session, &PLAYER2, Seat::Player2, start + 40);
+        assert_eq!(session.player1_time_left, player1_time - 30 + 5);
+        assert_eq!(session.player2_time_left, player2_time - 40 + 5);
+    }
+
+    #[test]
+    fn only_the_active_player_is_charged_during_the_action_phase() {
+        let session = plan(&timed_duel(), &[(0, straight(20.0)), (1, straight(20.0))]);
+        let (player1_time, player2_time) = (session.player1_time_left, session.player2_time_left);
+        let at = session.updated_at + 20;
+
+        let session = apply_at(&session, &PLAYER1, GameAction::Skip { ship_id: 0 }, at).unwrap();
+        assert_eq!(session.player1_time_left, player1_time - 20 + 5);
+        assert_eq!(session.player2_time_left, player2_time);
+    }
+
+    #[test]
+    fn flagging_a_clock_ends_the_game() {
+        let session = timed_duel();
+        let start = session.updated_at;
+        let out_of_time = start + session.player2_time_left;
+        let session = commit_nothing(&session, &PLAYER1, Seat::Player1, start + 10);
+
+        let result = apply_at(
+            &session,
+            &OUTSIDER,
+            GameAction::FlagClock {},
+            out_of_time - 1,
+        );
+        assert!(matches!(result, Err(GameError::DeadlineNotReached)));
+
+        // Player 1 finished in time, so only player 2's clock ran out
+        let (session, result) = session
+            .apply(&context(&OUTSIDER, out_of_time), GameAction::FlagClock {})
+            .unwrap();
+        assert_eq!(
+            result,
+            ActionResult::ClockFlagged {
+                losing_player: PLAYER2
+            }
+        );
+        assert!(session.is_finished());
+        assert_eq!(session.winning_player, PLAYER1);
+    }
+
+    #[test]
+    fn players_out_of_time_can_only_concede() {
+        let mut session = timed_duel();
+        session.player2_time_left = 0;
+
+        let commitment = commitment_for(&maneuvers_for(&session, &PLAYER2, &[]), Seat::Player2);
+        let result = apply(
+            &session,
+            &PLAYER2,
+            GameAction::CommitManeuvers { commitment },
+        );
+        assert!(matches!(result, Err(GameError::OutOfTime)));
+
+        let session = apply(&session, &PLAYER2, GameAction::Concede {}).unwrap();
+        assert_eq!(session.winning_player, PLAYER1);
+    }
+
+    #[test]
+    fn clocks_stand_still_without_a_time_bank() {
+        let session = duel(SessionRules::default());
+        let at = session.updated_at + 30;
+        let session = commit_nothing(&session, &PLAYER1, Seat::Player1, at);
+
+        assert_eq!(session.player1_time_left, 0);
+        assert_eq!(session.player2_time_left, 0);
+        assert_eq!(session.flagged_seat(), None);
+    }
+}
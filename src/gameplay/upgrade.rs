@@ -0,0 +1,244 @@
+use crate::error::GameError;
+use crate::gameplay::ship::Ship;
+use solana_program::msg;
+
+pub const UPGRADE_COUNT: usize = 9;
+
+// Hard limits no amount of upgrades can push a ship past
+pub const MAX_WEAPON_ARC: f32 = 180.0;
+pub const MAX_HIT_CHANCE: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpgradeEffect {
+    MaxSpeed(f32),
+    MaxAngle(f32),
+    WeaponArc(f32),
+    WeaponRange(f32),
+    WeaponDamage(f32),
+    HitChance(f32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UpgradeInfo {
+    pub id: u32,
+    pub name: &'static str,
+    pub effect: UpgradeEffect,
+    // How many times a single ship can install it
+    pub max_stacks: u8,
+    // Upgrade the ship needs to have installed first
+    pub prerequisite: Option<u32>,
+    // Fleet points it costs to install before the match
+    pub cost: u32,
+    // Scrap it costs to install during the match
+    pub scrap_cost: u32,
+}
+
+// Tech tree, the basic upgrades have no prerequisite and each unlocks a stronger one
+pub const UPGRADE_CATALOG: [UpgradeInfo; UPGRADE_COUNT] = [
+    UpgradeInfo {
+        id: 1,
+        name: "Speed",
+        effect: UpgradeEffect::MaxSpeed(10.0),
+        max_stacks: 2,
+        prerequisite: None,
+        cost: 8,
+        scrap_cost: 5,
+    },
+    UpgradeInfo {
+        id: 2,
+        name: "Agility",
+        effect: UpgradeEffect::MaxAngle(11.25),
+        max_stacks: 2,
+        prerequisite: None,
+        cost: 6,
+        scrap_cost: 4,
+    },
+    UpgradeInfo {
+        id: 3,
+        name: "Weapon Arc",
+        effect: UpgradeEffect::WeaponArc(12.5),
+        max_stacks: 3,
+        prerequisite: None,
+        cost: 6,
+        scrap_cost: 4,
+    },
+    UpgradeInfo {
+        id: 4,
+        name: "Weapon Range",
+        effect: UpgradeEffect::WeaponRange(10.0),
+        max_stacks: 2,
+        prerequisite: None,
+        cost: 8,
+        scrap_cost: 5,
+    },
+    UpgradeInfo {
+        id: 5,
+        name: "Weapon Damage",
+        effect: UpgradeEffect::WeaponDamage(20.0),
+        max_stacks: 2,
+        prerequisite: None,
+        cost: 10,
+        scrap_cost: 6,
+    },
+    UpgradeInfo {
+        id: 6,
+        name: "Weapon Hit Chance",
+        effect: UpgradeEffect::HitChance(0.1),
+        max_stacks: 2,
+        prerequisite: None,
+        cost: 10,
+        scrap_cost: 6,
+    },
+    UpgradeInfo {
+        id: 7,
+        name: "Thrust Vectoring",
+        effect: UpgradeEffect::MaxAngle(20.0),
+        max_stacks: 1,
+        prerequisite: Some(2),
+        cost: 10,
+        scrap_cost: 10,
+    },
+    UpgradeInfo {
+        id: 8,
+        name: "Runic Warheads",
+        effect: UpgradeEffect::WeaponDamage(30.0),
+        max_stacks: 1,
+        prerequisite: Some(5),
+        cost: 15,
+        scrap_cost: 12,
+    },
+    UpgradeInfo {
+        id: 9,
+        name: "Raven Targeting",
+        effect: UpgradeEffect::HitChance(0.15),
+        max_stacks: 1,
+        prerequisite: Some(6),
+        cost: 15,
+        scrap_cost: 12,
+    },
+];
+
+// Position of the upgrade in the catalog, which is also where a ship counts its stacks
+pub fn catalog_index(upgrade_id: u32) -> Option<usize> {
+    UPGRADE_CATALOG.iter().position(|u| u.id == upgrade_id)
+}
+
+pub fn find_upgrade(upgrade_id: u32) -> Option<&'static UpgradeInfo> {
+    catalog_index(upgrade_id).map(|i| &UPGRADE_CATALOG[i])
+}
+
+impl Ship {
+    pub fn upgrade_stacks(&self, upgrade_id: u32) -> u8 {
+        catalog_index(upgrade_id).map_or(0, |i| self.upgrade_stacks[i])
+    }
+
+    pub fn apply_upgrade(&mut self, upgrade_id: u32) -> Result<(), GameError> {
+        let (index, upgrade) = match catalog_index(upgrade_id) {
+            Some(index) => (index, UPGRADE_CATALOG[index]),
+            None => {
+                msg!("Invalid upgrade_id: {}", upgrade_id);
+                return Err(GameError::UnknownUpgrade);
+            }
+        };
+
+        if self.upgrade_stacks[index] >= upgrade.max_stacks {
+            msg!(
+                "Ship {} already has {} {} upgrades",
+                self.id,
+                upgrade.max_stacks,
+                upgrade.name
+            );
+            return Err(GameError::UpgradeLimitReached);
+        }
+
+        if let Some(prerequisite) = upgrade.prerequisite {
+            if self.upgrade_stacks(prerequisite) == 0 {
+                msg!("{} needs upgrade {} first", upgrade.name, prerequisite);
+                return Err(GameError::MissingPrerequisite);
+            }
+        }
+
+        msg!("Applying {} upgrade", upgrade.name);
+        match upgrade.effect {
+            UpgradeEffect::MaxSpeed(amount) => self.max_speed += amount,
+            UpgradeEffect::MaxAngle(amount) => self.max_angle += amount,
+            // Weapon upgrades improve every mount on the ship
+            UpgradeEffect::WeaponArc(amount) => {
+                for weapon in self.weapons.iter_mut() {
+                    weapon.arc = (weapon.arc + amount).min(MAX_WEAPON_ARC);
+                }
+            }
+            UpgradeEffect::WeaponRange(amount) => {
+                for weapon in self.weapons.iter_mut() {
+                    weapon.range += amount;
+                }
+            }
+            UpgradeEffect::WeaponDamage(amount) => {
+                for weapon in self.weapons.iter_mut() {
+                    weapon.damage += amount;
+                }
+            }
+            UpgradeEffect::HitChance(amount) => {
+                for weapon in self.weapons.iter_mut() {
+                    weapon.hit_chance = (weapon.hit_chance + amount).min(MAX_HIT_CHANCE);
+                }
+            }
+        }
+
+        self.upgrade_stacks[index] += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::test_session::*;
+
+    fn longship() -> Ship {
+        duel_in_action().ships[0].clone()
+    }
+
+    #[test]
+    fn upgrades_stack_up_to_their_limit() {
+        let mut ship = longship();
+        ship.apply_upgrade(1).unwrap();
+        ship.apply_upgrade(1).unwrap();
+        assert_eq!(ship.max_speed, 40.0);
+
+        let result = ship.apply_upgrade(1);
+        assert!(matches!(result, Err(GameError::UpgradeLimitReached)));
+        assert_eq!(ship.upgrade_stacks(1), 2);
+        assert_eq!(ship.max_speed, 40.0);
+    }
+
+    #[test]
+    fn advanced_upgrades_need_their_prerequisite() {
+        let mut ship = longship();
+        let result = ship.apply_upgrade(7);
+        assert!(matches!(result, Err(GameError::MissingPrerequisite)));
+
+        ship.apply_upgrade(2).unwrap();
+        ship.apply_upgrade(7).unwrap();
+        assert_eq!(ship.max_angle, 90.0 + 11.25 + 20.0);
+    }
+
+    #[test]
+    fn weapon_arc_and_hit_chance_are_capped() {
+        let mut ship = longship();
+        ship.weapons[0].arc = 175.0;
+        ship.weapons[0].hit_chance = 0.95;
+
+        ship.apply_upgrade(3).unwrap();
+        ship.apply_upgrade(6).unwrap();
+        assert_eq!(ship.weapons[0].arc, MAX_WEAPON_ARC);
+        assert_eq!(ship.weapons[0].hit_chance, MAX_HIT_CHANCE);
+    }
+
+    #[test]
+    fn unknown_upgrades_are_rejected() {
+        let mut ship = longship();
+        let result = ship.apply_upgrade(UPGRADE_COUNT as u32 + 1);
+        assert!(matches!(result, Err(GameError::UnknownUpgrade)));
+    }
+}
@@ -1,4 +1,7 @@
 use borsh::BorshDeserialize;
+use crate::gameplay::deployment::Placement;
+use crate::gameplay::fleet::ShipSpec;
+use crate::gameplay::session_rules::SessionRules;
 use crate::gameplay::ship::ManeuverData;
 use crate::utils::dictionary::Dictionary;
 use solana_program::program_error::ProgramError;
@@ -6,20 +9,52 @@ use solana_program::program_error::ProgramError;
 pub enum GameInstruction {
     JoinQueue {
         game_id: u64,
+        fleet: Vec<ShipSpec>,
+        rules: SessionRules,
     },
     LeaveQueue {},
-    CloseGame {
+    Concede {
         game_id: u64,
     },
+    Finalize {
+        game_id: u64,
+    },
+    CommitDeployment {
+        game_id: u64,
+        commitment: [u8; 32],
+    },
+    RevealDeployment {
+        game_id: u64,
+        placements: Dictionary<u32, Placement>,
+        salt: [u8; 32],
+    },
     CommitManeuvers {
+        game_id: u64,
+        commitment: [u8; 32],
+    },
+    RevealManeuvers {
         game_id: u64,
         maneuver_data: Dictionary<u32, ManeuverData>,
+        salt: [u8; 32],
+    },
+    ClaimTimeout {
+        game_id: u64,
+    },
+    FlagClock {
+        game_id: u64,
     },
     FireWeapon {
         game_id: u64,
         ship_id: u32,
+        mount_index: u32,
         target_id: u32,
     },
+    FireAtObstacle {
+        game_id: u64,
+        ship_id: u32,
+        mount_index: u32,
+        obstacle_id: u32,
+    },
     Upgrade {
         game_id: u64,
         ship_id: u32,
@@ -29,6 +64,18 @@ pub enum GameInstruction {
         game_id: u64,
         ship_id: u32,
     },
+    Repair {
+        game_id: u64,
+        ship_id: u32,
+    },
+    Brace {
+        game_id: u64,
+        ship_id: u32,
+    },
+    Evade {
+        game_id: u64,
+        ship_id: u32,
+    },
 }
 
 #[derive(BorshDeserialize)]
@@ -36,19 +83,55 @@ struct GameManagementPayload {
     game_id: u64,
 }
 
+#[derive(BorshDeserialize)]
+struct JoinQueuePayload {
+    game_id: u64,
+    fleet: Vec<ShipSpec>,
+    rules: SessionRules,
+}
+
+#[derive(BorshDeserialize)]
+struct CommitDeploymentPayload {
+    game_id: u64,
+    commitment: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct RevealDeploymentPayload {
+    game_id: u64,
+    placements: Dictionary<u32, Placement>,
+    salt: [u8; 32],
+}
+
 #[derive(BorshDeserialize)]
 struct CommitManeuversPayload {
+    game_id: u64,
+    commitment: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct RevealManeuversPayload {
     game_id: u64,
     maneuver_data: Dictionary<u32, ManeuverData>,
+    salt: [u8; 32],
 }
 
 #[derive(BorshDeserialize)]
 struct FireWeaponPayload {
     game_id: u64,
     ship_id: u32,
+    mount_index: u32,
     target_id: u32,
 }
 
+#[derive(BorshDeserialize)]
+struct FireAtObstaclePayload {
+    game_id: u64,
+    ship_id: u32,
+    mount_index: u32,
+    obstacle_id: u32,
+}
+
 #[derive(BorshDeserialize)]
 struct UpgradePayload {
     game_id: u64,
@@ -62,6 +145,24 @@ struct SkipPayload {
     ship_id: u32,
 }
 
+#[derive(BorshDeserialize)]
+struct RepairPayload {
+    game_id: u64,
+    ship_id: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct BracePayload {
+    game_id: u64,
+    ship_id: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct EvadePayload {
+    game_id: u64,
+    ship_id: u32,
+}
+
 impl GameInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (&variant, rest) = input
@@ -70,15 +171,17 @@ impl GameInstruction {
 
         match variant {
             11 => {
-                let payload = GameManagementPayload::try_from_slice(rest).unwrap();
+                let payload = JoinQueuePayload::try_from_slice(rest).unwrap();
                 Ok(Self::JoinQueue {
                     game_id: payload.game_id,
+                    fleet: payload.fleet,
+                    rules: payload.rules,
                 })
             }
             12 => Ok(Self::LeaveQueue {}),
             21 => {
                 let payload = GameManagementPayload::try_from_slice(rest).unwrap();
-                Ok(Self::CloseGame {
+                Ok(Self::Concede {
                     game_id: payload.game_id,
                 })
             }
@@ -94,7 +197,7 @@ impl GameInstruction {
                 let payload = CommitManeuversPayload::try_from_slice(rest).unwrap();
                 Ok(Self::CommitManeuvers {
                     game_id: payload.game_id,
-                    maneuver_data: payload.maneuver_data,
+                    commitment: payload.commitment,
                 })
             }
             24 => {
@@ -102,6 +205,7 @@ impl GameInstruction {
                 Ok(Self::FireWeapon {
                     game_id: payload.game_id,
                     ship_id: payload.ship_id,
+                    mount_index: payload.mount_index,
                     target_id: payload.target_id,
                 })
             }
@@ -112,6 +216,77 @@ impl GameInstruction {
                     ship_id: payload.ship_id,
                 })
             }
+            26 => {
+                let payload = RevealManeuversPayload::try_from_slice(rest).unwrap();
+                Ok(Self::RevealManeuvers {
+                    game_id: payload.game_id,
+                    maneuver_data: payload.maneuver_data,
+                    salt: payload.salt,
+                })
+            }
+            27 => {
+                let payload = GameManagementPayload::try_from_slice(rest).unwrap();
+                Ok(Self::ClaimTimeout {
+                    game_id: payload.game_id,
+                })
+            }
+            28 => {
+                let payload = GameManagementPayload::try_from_slice(rest).unwrap();
+                Ok(Self::Finalize {
+                    game_id: payload.game_id,
+                })
+            }
+            29 => {
+                let payload = GameManagementPayload::try_from_slice(rest).unwrap();
+                Ok(Self::FlagClock {
+                    game_id: payload.game_id,
+                })
+            }
+            30 => {
+                let payload = FireAtObstaclePayload::try_from_slice(rest).unwrap();
+                Ok(Self::FireAtObstacle {
+                    game_id: payload.game_id,
+                    ship_id: payload.ship_id,
+                    mount_index: payload.mount_index,
+                    obstacle_id: payload.obstacle_id,
+                })
+            }
+            31 => {
+                let payload = CommitDeploymentPayload::try_from_slice(rest).unwrap();
+                Ok(Self::CommitDeployment {
+                    game_id: payload.game_id,
+                    commitment: payload.commitment,
+                })
+            }
+            32 => {
+                let payload = RevealDeploymentPayload::try_from_slice(rest).unwrap();
+                Ok(Self::RevealDeployment {
+                    game_id: payload.game_id,
+                    placements: payload.placements,
+                    salt: payload.salt,
+                })
+            }
+            33 => {
+                let payload = RepairPayload::try_from_slice(rest).unwrap();
+                Ok(Self::Repair {
+                    game_id: payload.game_id,
+                    ship_id: payload.ship_id,
+                })
+            }
+            34 => {
+                let payload = BracePayload::try_from_slice(rest).unwrap();
+                Ok(Self::Brace {
+                    game_id: payload.game_id,
+                    ship_id: payload.ship_id,
+                })
+            }
+            35 => {
+                let payload = EvadePayload::try_from_slice(rest).unwrap();
+                Ok(Self::Evade {
+                    game_id: payload.game_id,
+                    ship_id: payload.ship_id,
+                })
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         }
     }
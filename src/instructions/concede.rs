@@ -0,0 +1,30 @@
+use crate::gameplay::game_session;
+use crate::gameplay::rules::GameAction;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> ProgramResult {
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+
+    // Signer should be the same as the initializer
+    if !initializer.is_signer {
+        msg!("Initializer {} should be the signer", initializer.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Get session data
+    let session_data = game_session::get_or_create_data(program_id, accounts, game_id)?;
+
+    // Apply the rules
+    let context = game_session::get_action_context(initializer)?;
+    let (session_data, _) = session_data.apply(&context, GameAction::Concede {})?;
+
+    game_session::write_data(accounts, &session_data)
+}
@@ -0,0 +1,93 @@
+use crate::error::GameError;
+use crate::gameplay::game_session;
+use crate::gameplay::matchmaking;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+// Anyone can finalize a finished game. Everyone who paid for the account to grow gets that back, and
+// the rest of the rent goes to whoever created the session. Their accounts follow the rent payer
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> ProgramResult {
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let matchmaking_account = next_account_info(account_info_iter)?;
+    let game_session_account = next_account_info(account_info_iter)?;
+    let rent_payer = next_account_info(account_info_iter)?;
+
+    // Signer should be the same as the initializer
+    if !initializer.is_signer {
+        msg!("Initializer {} should be the signer", initializer.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Get matchmaking data
+    let mut matchmaking_data = matchmaking::get_or_create_data(
+        program_id,
+        &[
+            initializer.clone(),
+            system_program.clone(),
+            matchmaking_account.clone(),
+        ],
+    )?;
+
+    // Get session data
+    let session_data = game_session::get_or_create_data(
+        program_id,
+        &[
+            initializer.clone(),
+            system_program.clone(),
+            game_session_account.clone(),
+        ],
+        game_id,
+    )?;
+
+    if !session_data.is_finished() {
+        msg!("Game can only be finalized once there is a winner");
+        return Err(GameError::WrongPhase.into());
+    }
+
+    if *rent_payer.key != session_data.rent_payer {
+        msg!(
+            "Rent should go to {} but received {}",
+            session_data.rent_payer,
+            rent_payer.key
+        );
+        return Err(GameError::InvalidRentPayer.into());
+    }
+
+    // Only remove the players if they haven't moved on to another game already
+    for player in [session_data.player1, session_data.player2] {
+        if matchmaking_data.active_games.get(&player) == Some(&game_id) {
+            matchmaking_data.active_games.remove(&player);
+        }
+    }
+
+    matchmaking::write_data(
+        &[
+            initializer.clone(),
+            system_program.clone(),
+            matchmaking_account.clone(),
+        ],
+        &matchmaking_data,
+    )?;
+
+    // Close pda
+    let mut close_accounts = vec![
+        rent_payer.clone(),
+        system_program.clone(),
+        game_session_account.clone(),
+    ];
+    close_accounts.extend(account_info_iter.cloned());
+    game_session::close_pda(
+        program_id,
+        &close_accounts,
+        game_id,
+        &session_data.rent_contributions,
+    )
+}
@@ -0,0 +1,50 @@
+use crate::gameplay::game_session;
+use crate::gameplay::rules::GameAction;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_id: u64,
+    ship_id: u32,
+    mount_index: u32,
+    obstacle_id: u32,
+) -> ProgramResult {
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+    let _system_program = next_account_info(account_info_iter)?;
+    let _game_session_account = next_account_info(account_info_iter)?;
+    let slot_hashes_account = next_account_info(account_info_iter)?;
+    let instructions_account = next_account_info(account_info_iter)?;
+
+    // Signer should be the same as the initializer
+    if !initializer.is_signer {
+        msg!("Initializer {} should be the signer", initializer.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Get session data
+    let session_data = game_session::get_or_create_data(program_id, accounts, game_id)?;
+
+    // Apply the rules
+    let mut context = game_session::get_action_context(initializer)?;
+    context.recent_slot_hash =
+        game_session::get_recent_slot_hash(slot_hashes_account, instructions_account)?;
+    let (session_data, _) = session_data.apply(
+        &context,
+        GameAction::FireAtObstacle {
+            ship_id,
+            mount_index,
+            obstacle_id,
+        },
+    )?;
+
+    game_session::write_data(accounts, &session_data)
+}
@@ -1,15 +1,25 @@
+use crate::gameplay::fleet::{self, ShipSpec};
 use crate::gameplay::game_session;
 use crate::gameplay::matchmaking;
-use crate::gameplay::ship::{ManeuverData, Ship, WeaponData};
+use crate::gameplay::obstacle;
+use crate::gameplay::phase::GamePhase;
+use crate::gameplay::session_rules::SessionRules;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
 };
 
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> ProgramResult {
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_id: u64,
+    fleet: Vec<ShipSpec>,
+    rules: SessionRules,
+) -> ProgramResult {
     // Get accounts
     let account_info_iter = &mut accounts.iter();
     let initializer = next_account_info(account_info_iter)?;
@@ -34,13 +44,24 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> P
 
     // TODO: Can't join if already in queue or in active game
 
-    // Try make match
-    if matchmaking_data.queue.len() < 1 {
-        matchmaking_data.add_to_queue(initializer.key);
-    } else {
+    fleet::validate_fleet(
+        &fleet,
+        matchmaking_data.min_fleet_size,
+        matchmaking_data.max_fleet_size,
+        matchmaking_data.fleet_budget,
+    )?;
+    rules.validate()?;
+
+    // Try make match with someone who picked the same rules
+    if let Some(player2) = matchmaking_data.find_opponent(initializer.key, &rules) {
         let player1 = *initializer.key;
-        let player2 = matchmaking_data.queue.remove(0);
-        matchmaking_data.queue.retain(|&pubkey| pubkey != player2);
+        let player1_fleet = fleet;
+        let player2_fleet = matchmaking_data
+            .fleets
+            .get(&player2)
+            .cloned()
+            .unwrap_or_default();
+        matchmaking_data.leave_queue(&player2);
 
         // Add both players to the active games list so they can find the id of their active game
         matchmaking_data.active_games.insert(player1, game_id);
@@ -58,160 +79,19 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> P
         )?;
 
         // Initialize game session
+        game_session_data.rules = rules;
         game_session_data.player1 = player1;
         game_session_data.player2 = player2;
         game_session_data.active_player = player1;
+        game_session_data.updated_at = Clock::get()?.unix_timestamp;
+        game_session_data.player1_time_left = game_session_data.rules.time_bank;
+        game_session_data.player2_time_left = game_session_data.rules.time_bank;
+        game_session_data.player1_scrap = game_session_data.rules.starting_scrap;
+        game_session_data.player2_scrap = game_session_data.rules.starting_scrap;
+        game_session_data.transition(GamePhase::deployment())?;
 
-        let health = 100.0;
-        let min_speed = 10.0;
-        let max_speed = 20.0;
-        let min_angle = 0.0;
-        let max_angle = 90.0;
-        let weapon_arc = 45.0;
-        let weapon_range = 30.0;
-        let weapon_damage = 40.0;
-        let hit_chance = 0.7;
-
-        game_session_data.ships = vec![
-            Ship {
-                id: 0,
-                owner: player1,
-                x_position: 0.0,
-                y_position: -20.0,
-                rotation: 90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-            Ship {
-                id: 1,
-                owner: player1,
-                x_position: -20.0,
-                y_position: -20.0,
-                rotation: 90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-            Ship {
-                id: 2,
-                owner: player1,
-                x_position: -40.0,
-                y_position: -20.0,
-                rotation: 90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-            Ship {
-                id: 3,
-                owner: player2,
-                x_position: 0.0,
-                y_position: 40.0,
-                rotation: -90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-            Ship {
-                id: 4,
-                owner: player2,
-                x_position: 20.0,
-                y_position: 40.0,
-                rotation: -90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-            Ship {
-                id: 5,
-                owner: player2,
-                x_position: 40.0,
-                y_position: 40.0,
-                rotation: -90.0,
-                health: health,
-                min_speed: min_speed,
-                max_speed: max_speed,
-                min_angle: min_angle,
-                max_angle: max_angle,
-                maneuver_data: ManeuverData {
-                    angle: 0.0,
-                    speed: 0.0,
-                },
-                weapon_data: WeaponData {
-                    arc: weapon_arc,
-                    range: weapon_range,
-                    damage: weapon_damage,
-                    hit_chance: hit_chance,
-                },
-                activated: false,
-            },
-        ];
+        game_session_data.spawn_fleets(&player1_fleet, &player2_fleet)?;
+        game_session_data.obstacles = obstacle::default_layout();
 
         // Write data to pda
         game_session::write_data(
@@ -222,6 +102,8 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], game_id: u64) -> P
             ],
             &game_session_data,
         )?;
+    } else {
+        matchmaking_data.add_to_queue(initializer.key, fleet, rules);
     }
 
     // Write data to pda
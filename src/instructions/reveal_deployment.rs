@@ -0,0 +1,39 @@
+use crate::gameplay::deployment::Placement;
+use crate::gameplay::game_session;
+use crate::gameplay::rules::GameAction;
+use crate::utils::dictionary::Dictionary;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_id: u64,
+    placements: Dictionary<u32, Placement>,
+    salt: [u8; 32],
+) -> ProgramResult {
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let initializer = next_account_info(account_info_iter)?;
+
+    // Signer should be the same as the initializer
+    if !initializer.is_signer {
+        msg!("Initializer {} should be the signer", initializer.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Get session data
+    let session_data = game_session::get_or_create_data(program_id, accounts, game_id)?;
+
+    // Apply the rules
+    let context = game_session::get_action_context(initializer)?;
+    let (session_data, _) =
+        session_data.apply(&context, GameAction::RevealDeployment { placements, salt })?;
+
+    game_session::write_data(accounts, &session_data)
+}
@@ -1,5 +1,5 @@
-use crate::error::GameError;
 use crate::gameplay::game_session;
+use crate::gameplay::rules::GameAction;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -25,79 +25,11 @@ pub fn process(
     }
 
     // Get session data
-    let mut session_data = game_session::get_or_create_data(program_id, accounts, game_id)?;
+    let session_data = game_session::get_or_create_data(program_id, accounts, game_id)?;
 
-    if session_data.winning_player != Pubkey::default() {
-        msg!("Game is already finished");
-        return Err(GameError::InvalidShip.into());
-    }
-
-    if *initializer.key != session_data.active_player {
-        msg!("Not the active player");
-        return Err(GameError::InvalidShip.into());
-    }
-
-    if ship_id >= session_data.ships.len() as u32 {
-        msg!("Invalid ship id");
-        return Err(GameError::InvalidTarget.into());
-    }
-
-    let ship = session_data.ships[ship_id as usize];
-    if ship.owner != *initializer.key {
-        msg!("You can only activate your own ship");
-        return Err(GameError::InvalidShip.into());
-    }
-
-    if ship.activated {
-        msg!("Ship already activated");
-        return Err(GameError::InvalidShip.into());
-    }
-
-    if ship.health <= 0.0 {
-        msg!("Ship is destroyed");
-        return Err(GameError::InvalidShip.into());
-    }
-
-    session_data.last_action = 0;
-    session_data.last_action_data = vec![ship_id];
-
-    if let Some(ship) = session_data.ships.get_mut(ship_id as usize) {
-        // Mark ship as activated
-        ship.activated = true;
-    }
-
-    // This means all ships have activated
-    if session_data
-        .ships
-        .iter()
-        .all(|s| s.health <= 0.0 || s.activated)
-    {
-        session_data.turn = session_data.turn + 1;
-        session_data.active_player = session_data.player1;
-        session_data.phase = 0;
-
-        // Reset ship activation, but only if ship isn't destroyed
-        for s in &mut session_data.ships {
-            if s.health <= 0.0 {
-                continue;
-            }
-
-            s.activated = false;
-        }
-    } else {
-        // Toggle active player, but only if the other player has at least one non-activated ship
-        if session_data
-            .ships
-            .iter()
-            .any(|s| s.owner != session_data.active_player && s.health > 0.0 && !s.activated)
-        {
-            if session_data.active_player == session_data.player1 {
-                session_data.active_player = session_data.player2;
-            } else {
-                session_data.active_player = session_data.player1;
-            }
-        }
-    }
+    // Apply the rules
+    let context = game_session::get_action_context(initializer)?;
+    let (session_data, _) = session_data.apply(&context, GameAction::Skip { ship_id })?;
 
     game_session::write_data(accounts, &session_data)
 }
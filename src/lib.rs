@@ -5,24 +5,55 @@ pub mod macros;
 pub mod processor;
 
 pub mod instructions {
-    pub mod close_game;
+    pub mod brace;
+    pub mod claim_timeout;
+    pub mod commit_deployment;
     pub mod commit_maneuvers;
+    pub mod concede;
+    pub mod evade;
+    pub mod finalize;
+    pub mod fire_at_obstacle;
     pub mod fire_weapon;
+    pub mod flag_clock;
     pub mod join_queue;
     pub mod leave_queue;
+    pub mod repair;
+    pub mod reveal_deployment;
+    pub mod reveal_maneuvers;
     pub mod skip;
     pub mod upgrade;
 }
 
 pub mod utils {
+    pub mod commitment;
     pub mod dictionary;
     pub mod random;
     pub mod vec2;
 }
 
 pub mod gameplay {
+    pub mod arena;
+    pub mod commit_reveal;
+    pub mod critical;
+    pub mod damage;
+    pub mod deployment;
+    pub mod fleet;
     pub mod game_session;
+    pub mod line_of_sight;
     pub mod matchmaking;
+    pub mod movement;
     pub mod obstacle;
+    pub mod phase;
+    pub mod rules;
+    pub mod scrap;
+    pub mod seat;
+    pub mod session_rules;
     pub mod ship;
+    pub mod ship_class;
+    pub mod shot;
+    pub mod storm;
+    #[cfg(test)]
+    pub mod test_session;
+    pub mod time_bank;
+    pub mod upgrade;
 }
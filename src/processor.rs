@@ -1,6 +1,8 @@
 use crate::instruction::GameInstruction;
 use crate::instructions::{
-    close_game, commit_maneuvers, fire_weapon, join_queue, leave_queue, skip, upgrade,
+    brace, claim_timeout, commit_deployment, commit_maneuvers, concede, evade, finalize,
+    fire_at_obstacle, fire_weapon, flag_clock, join_queue, leave_queue, repair, reveal_deployment,
+    reveal_maneuvers, skip, upgrade,
 };
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
@@ -11,25 +13,76 @@ pub fn process_instruction(
 ) -> ProgramResult {
     let instruction = GameInstruction::unpack(instruction_data)?;
     match instruction {
-        GameInstruction::JoinQueue { game_id } => {
-            join_queue::process(program_id, accounts, game_id)
-        }
+        GameInstruction::JoinQueue {
+            game_id,
+            fleet,
+            rules,
+        } => join_queue::process(program_id, accounts, game_id, fleet, rules),
         GameInstruction::LeaveQueue {} => leave_queue::process(program_id, accounts),
-        GameInstruction::CloseGame { game_id } => {
-            close_game::process(program_id, accounts, game_id)
-        }
+        GameInstruction::Concede { game_id } => concede::process(program_id, accounts, game_id),
+        GameInstruction::Finalize { game_id } => finalize::process(program_id, accounts, game_id),
+        GameInstruction::CommitDeployment {
+            game_id,
+            commitment,
+        } => commit_deployment::process(program_id, accounts, game_id, commitment),
+        GameInstruction::RevealDeployment {
+            game_id,
+            placements,
+            salt,
+        } => reveal_deployment::process(program_id, accounts, game_id, placements, salt),
         GameInstruction::CommitManeuvers {
+            game_id,
+            commitment,
+        } => commit_maneuvers::process(program_id, accounts, game_id, commitment),
+        GameInstruction::RevealManeuvers {
             game_id,
             maneuver_data,
-        } => commit_maneuvers::process(program_id, accounts, game_id, maneuver_data),
+            salt,
+        } => reveal_maneuvers::process(program_id, accounts, game_id, maneuver_data, salt),
+        GameInstruction::ClaimTimeout { game_id } => {
+            claim_timeout::process(program_id, accounts, game_id)
+        }
+        GameInstruction::FlagClock { game_id } => {
+            flag_clock::process(program_id, accounts, game_id)
+        }
         GameInstruction::FireWeapon {
             game_id,
             ship_id,
+            mount_index,
+            target_id,
+        } => fire_weapon::process(
+            program_id,
+            accounts,
+            game_id,
+            ship_id,
+            mount_index,
             target_id,
-        } => fire_weapon::process(program_id, accounts, game_id, ship_id, target_id),
+        ),
+        GameInstruction::FireAtObstacle {
+            game_id,
+            ship_id,
+            mount_index,
+            obstacle_id,
+        } => fire_at_obstacle::process(
+            program_id,
+            accounts,
+            game_id,
+            ship_id,
+            mount_index,
+            obstacle_id,
+        ),
         GameInstruction::Skip { game_id, ship_id } => {
             skip::process(program_id, accounts, game_id, ship_id)
         }
+        GameInstruction::Repair { game_id, ship_id } => {
+            repair::process(program_id, accounts, game_id, ship_id)
+        }
+        GameInstruction::Brace { game_id, ship_id } => {
+            brace::process(program_id, accounts, game_id, ship_id)
+        }
+        GameInstruction::Evade { game_id, ship_id } => {
+            evade::process(program_id, accounts, game_id, ship_id)
+        }
         GameInstruction::Upgrade {
             game_id,
             ship_id,
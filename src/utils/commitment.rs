@@ -0,0 +1,10 @@
+use solana_program::hash::hashv;
+
+// Hash of some data together with a secret salt, the salt keeps the data from being guessed from the hash
+pub fn commit(data: &[u8], salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[data, salt.as_ref()]).to_bytes()
+}
+
+pub fn verify(commitment: &[u8; 32], data: &[u8], salt: &[u8; 32]) -> bool {
+    commit(data, salt) == *commitment
+}
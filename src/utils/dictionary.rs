@@ -78,6 +78,19 @@ where
     }
 }
 
+impl<K, V> Dictionary<K, V>
+where
+    K: BorshSerialize + BorshDeserialize + Clone + Eq + Hash + Ord,
+    V: BorshSerialize + BorshDeserialize + Clone,
+{
+    // Same as as_vec, but in a fixed order so the result can be hashed
+    pub fn as_sorted_vec(&self) -> Vec<(K, V)> {
+        let mut vec = self.as_vec();
+        vec.sort_by(|a, b| a.0.cmp(&b.0));
+        vec
+    }
+}
+
 impl<K, V> std::ops::Index<K> for Dictionary<K, V>
 where
     K: BorshSerialize + BorshDeserialize + Clone + Eq + Hash,
@@ -1,4 +1,5 @@
-use solana_program::pubkey::Pubkey;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::hashv, pubkey::Pubkey};
 
 pub struct SplitMix64 {
     pub seed: u64,
@@ -7,7 +8,10 @@ pub struct SplitMix64 {
 
 impl SplitMix64 {
     pub fn new(seed: &Pubkey) -> Self {
-        let seed_bytes = seed.to_bytes();
+        Self::from_bytes(&seed.to_bytes())
+    }
+
+    pub fn from_bytes(seed_bytes: &[u8; 32]) -> Self {
         let mut seed_num: u64 = 0;
         for i in 0..8 {
             seed_num = (seed_num << 8) + (seed_bytes[i] as u64);
@@ -46,3 +50,52 @@ impl SplitMix64 {
         float_value
     }
 }
+
+// Everything that went into a roll, so anyone can recompute it afterwards
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RollRecord {
+    pub entropy: [u8; 32],
+    pub slot_hash: [u8; 32],
+    pub nonce: u64,
+    pub subject: [u32; 3],
+    pub value: f64,
+}
+
+// Fold a contribution into the game's entropy pool
+pub fn mix_entropy(entropy: &[u8; 32], contribution: &[u8; 32]) -> [u8; 32] {
+    hashv(&[entropy.as_ref(), contribution.as_ref()]).to_bytes()
+}
+
+// Seed for a single roll, the nonce makes sure no two rolls in a game share a seed
+// and the subject ties the roll to the ship, target and mount it was made for
+pub fn roll_seed(
+    entropy: &[u8; 32],
+    slot_hash: &[u8; 32],
+    nonce: u64,
+    subject: &[u32; 3],
+) -> [u8; 32] {
+    let subject_bytes: Vec<u8> = subject.iter().flat_map(|x| x.to_le_bytes()).collect();
+    hashv(&[
+        entropy.as_ref(),
+        slot_hash.as_ref(),
+        nonce.to_le_bytes().as_ref(),
+        subject_bytes.as_ref(),
+    ])
+    .to_bytes()
+}
+
+pub fn roll(
+    entropy: &[u8; 32],
+    slot_hash: &[u8; 32],
+    nonce: u64,
+    subject: &[u32; 3],
+) -> RollRecord {
+    let seed = roll_seed(entropy, slot_hash, nonce, subject);
+    RollRecord {
+        entropy: *entropy,
+        slot_hash: *slot_hash,
+        nonce,
+        subject: *subject,
+        value: SplitMix64::from_bytes(&seed).next_double(),
+    }
+}
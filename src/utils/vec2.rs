@@ -1,3 +1,6 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
@@ -14,6 +17,16 @@ impl Vector2 {
         self.sqr_magnitude().sqrt()
     }
 
+    // Same direction with a length of 1, or zero for the zero vector
+    pub fn normalized(&self) -> Vector2 {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            Vector2 { x: 0.0, y: 0.0 }
+        } else {
+            self.scale(1.0 / magnitude)
+        }
+    }
+
     // Calculate the dot product of two vectors
     pub fn dot_product(&self, other: &Vector2) -> f32 {
         self.x * other.x + self.y * other.y
@@ -47,4 +60,30 @@ impl Vector2 {
             y: self.y - other.y,
         }
     }
+
+    // Scale the vector by a scalar
+    pub fn scale(&self, factor: f32) -> Vector2 {
+        Vector2 {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+
+    // Find the point on the line segment between start and end that is closest to this point
+    pub fn closest_on_segment(&self, start: &Vector2, end: &Vector2) -> Vector2 {
+        let segment = end.subtract(start);
+        let length_squared = segment.sqr_magnitude();
+        if length_squared == 0.0 {
+            return *start;
+        }
+
+        let t = (self.subtract(start).dot_product(&segment) / length_squared).clamp(0.0, 1.0);
+        start.add(&segment.scale(t))
+    }
+
+    // Calculate the shortest distance from this point to the line segment between start and end
+    pub fn distance_to_segment(&self, start: &Vector2, end: &Vector2) -> f32 {
+        let closest = self.closest_on_segment(start, end);
+        self.subtract(&closest).magnitude()
+    }
 }